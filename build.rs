@@ -0,0 +1,28 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+/// Generate the C header of the `ffi` feature into `OUT_DIR`.
+///
+/// The committed copy in `include/` is checked against it by `tests/ffi.rs`.
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("missing CARGO_MANIFEST_DIR");
+    let out_dir = std::env::var("OUT_DIR").expect("missing OUT_DIR");
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("failed to read cbindgen.toml");
+
+    // Only the C ABI, and not every public constant of the library
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(format!("{}/src/ffi.rs", crate_dir))
+        .generate()
+        .expect("failed to generate C header")
+        .write_to_file(format!("{}/dbd_escape_chance_calculator.h", out_dir));
+
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+}
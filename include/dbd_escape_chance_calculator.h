@@ -0,0 +1,245 @@
+#ifndef DBD_ESCAPE_CHANCE_CALCULATOR_H
+#define DBD_ESCAPE_CHANCE_CALCULATOR_H
+
+/* Generated by cbindgen from src/ffi.rs when building with the `ffi` feature. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The live patch
+ */
+#define DBD_PATCH_LIVE 0
+
+/**
+ * The original patch, with flat Up the Ante
+ */
+#define DBD_PATCH_ORIGINAL 1
+
+/**
+ * Slippery Meat
+ */
+#define DBD_PERK_SLIPPERY_MEAT 0
+
+/**
+ * Up the Ante
+ */
+#define DBD_PERK_UP_THE_ANTE 1
+
+/**
+ * No offering
+ */
+#define DBD_OFFERING_NONE 0
+
+/**
+ * Chalk Pouch
+ */
+#define DBD_OFFERING_CHALK_POUCH 1
+
+/**
+ * Cream Chalk Pouch
+ */
+#define DBD_OFFERING_CREAM_CHALK_POUCH 2
+
+/**
+ * Ivory Chalk Pouch
+ */
+#define DBD_OFFERING_IVORY_CHALK_POUCH 3
+
+/**
+ * Salt Pouch
+ */
+#define DBD_OFFERING_SALT_POUCH 4
+
+/**
+ * Black Salt Statuette
+ */
+#define DBD_OFFERING_BLACK_SALT_STATUETTE 5
+
+/**
+ * Vigo's Jar of Salty Lips
+ */
+#define DBD_OFFERING_VIGOS_JAR_OF_SALTY_LIPS 6
+
+/**
+ * The result of a function that can fail
+ */
+typedef enum DbdStatus {
+  /**
+   * The function succeeded
+   */
+  DBD_STATUS_OK = 0,
+  /**
+   * A required pointer was null
+   */
+  DBD_STATUS_NULL_POINTER = 1,
+  /**
+   * An argument was out of range, like a tier of 4
+   */
+  DBD_STATUS_INVALID_ARGUMENT = 2,
+  /**
+   * The settings don't describe a legal trial
+   */
+  DBD_STATUS_INVALID_SETTINGS = 3,
+  /**
+   * Every perk slot of the survivor is full
+   */
+  DBD_STATUS_PERK_SLOTS_FULL = 4,
+} DbdStatus;
+
+/**
+ * Opaque settings for a trial
+ */
+typedef struct DbdSettings DbdSettings;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Make settings with 4 alive survivors and no perks or offerings, with survivor 0 on the hook.
+ *
+ * Free them with [`dbd_settings_free`].
+ */
+struct DbdSettings *dbd_settings_new(void);
+
+/**
+ * Make settings from aggregate counts, writing them to `out`.
+ *
+ * Survivor 0 is hooked and has Slippery Meat.
+ * Up the Ante is given to survivors 0, 1, 2 and 3 in order, so it can't outnumber the alive survivors.
+ * Salty Lips are burned starting from survivor 0.
+ * Survivors past the # of alive survivors are dead.
+ * Free the settings with [`dbd_settings_free`].
+ *
+ * Returns [`DbdStatus::InvalidArgument`] for a tier out of range, or an Up the Ante tier of 0.
+ * Returns [`DbdStatus::InvalidSettings`] if `up_the_ante_len` is more than `num_alive_survivors`,
+ * or for more than 4 Salty Lips or a # of alive survivors outside 1 to 4.
+ *
+ * # Safety
+ * `up_the_ante` must point to `up_the_ante_len` tiers, and may only be null if the length is 0.
+ * `out` must be null or valid for writes.
+ */
+enum DbdStatus dbd_settings_from_counts(uint8_t slippery_meat,
+                                        const uint8_t *up_the_ante,
+                                        size_t up_the_ante_len,
+                                        uint8_t num_salty_lips,
+                                        uint8_t num_alive_survivors,
+                                        struct DbdSettings **out);
+
+/**
+ * Copy settings, or return null if `settings` is null.
+ *
+ * # Safety
+ * `settings` must be null or a valid pointer from this library.
+ */
+struct DbdSettings *dbd_settings_clone(const struct DbdSettings *settings);
+
+/**
+ * Free settings. Passing null does nothing.
+ *
+ * # Safety
+ * `settings` must be null or a pointer from this library that has not been freed.
+ */
+void dbd_settings_free(struct DbdSettings *settings);
+
+/**
+ * Set which survivor is on the hook.
+ *
+ * # Safety
+ * `settings` must be null or a valid pointer from this library.
+ */
+enum DbdStatus dbd_settings_set_hooked_survivor(struct DbdSettings *settings, uint8_t survivor);
+
+/**
+ * Set whether a survivor is alive.
+ *
+ * # Safety
+ * `settings` must be null or a valid pointer from this library.
+ */
+enum DbdStatus dbd_settings_set_alive(struct DbdSettings *settings, uint8_t survivor, bool alive);
+
+/**
+ * Set the tier of a survivor's perk, equipping or unequipping it as needed.
+ *
+ * # Safety
+ * `settings` must be null or a valid pointer from this library.
+ */
+enum DbdStatus dbd_settings_set_perk_tier(struct DbdSettings *settings,
+                                          uint8_t survivor,
+                                          uint8_t perk,
+                                          uint8_t tier);
+
+/**
+ * Set the offering a survivor burned.
+ *
+ * # Safety
+ * `settings` must be null or a valid pointer from this library.
+ */
+enum DbdStatus dbd_settings_set_offering(struct DbdSettings *settings,
+                                         uint8_t survivor,
+                                         uint8_t offering);
+
+/**
+ * Increase the Slippery Meat tier of the hooked survivor.
+ *
+ * # Safety
+ * `settings` must be null or a valid pointer from this library.
+ */
+enum DbdStatus dbd_settings_increment_slippery_meat_tier(struct DbdSettings *settings);
+
+/**
+ * Decrease the Slippery Meat tier of the hooked survivor.
+ *
+ * # Safety
+ * `settings` must be null or a valid pointer from this library.
+ */
+enum DbdStatus dbd_settings_decrement_slippery_meat_tier(struct DbdSettings *settings);
+
+/**
+ * Increase the Up the Ante tier of a survivor.
+ *
+ * # Safety
+ * `settings` must be null or a valid pointer from this library.
+ */
+enum DbdStatus dbd_settings_increment_up_the_ante_tier(struct DbdSettings *settings,
+                                                       uint8_t survivor);
+
+/**
+ * Decrease the Up the Ante tier of a survivor.
+ *
+ * # Safety
+ * `settings` must be null or a valid pointer from this library.
+ */
+enum DbdStatus dbd_settings_decrement_up_the_ante_tier(struct DbdSettings *settings,
+                                                       uint8_t survivor);
+
+/**
+ * Calculate the escape chance with the rules of a patch, writing it to `out`.
+ *
+ * # Safety
+ * `settings` must be null or a valid pointer from this library.
+ * `out` must be null or valid for writes.
+ */
+enum DbdStatus dbd_calculate(const struct DbdSettings *settings, uint8_t patch, double *out);
+
+/**
+ * Get the escape chance of a single attempt and the # of attempts with the rules of a patch.
+ *
+ * # Safety
+ * `settings` must be null or a valid pointer from this library.
+ * The out pointers must be null or valid for writes.
+ */
+enum DbdStatus dbd_distribution(const struct DbdSettings *settings,
+                                uint8_t patch,
+                                double *escape_chance_per_attempt,
+                                uint8_t *num_tries);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* DBD_ESCAPE_CHANCE_CALCULATOR_H */
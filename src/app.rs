@@ -0,0 +1,503 @@
+//! The GUI.
+
+use crate::assets::Assets;
+use crate::assets::PerkImages;
+use crate::offering_picker::OfferingPicker;
+use crate::offering_picker::OfferingPickerState;
+use crate::perk_picker::PerkPicker;
+use crate::perk_picker::PerkPickerState;
+use anyhow::Context;
+use dbd_escape_chance_calculator::build_code;
+use dbd_escape_chance_calculator::chance_format::Chance;
+use dbd_escape_chance_calculator::chance_format::ChanceStyle;
+use dbd_escape_chance_calculator::explanation;
+use dbd_escape_chance_calculator::hook_escape_chance_settings::HookEscapeChanceSettings;
+use dbd_escape_chance_calculator::offering::Offering;
+use dbd_escape_chance_calculator::preferences::Preferences;
+use dbd_escape_chance_calculator::rule_set::Patch;
+use dbd_escape_chance_calculator::sensitivity;
+use dbd_escape_chance_calculator::survivor::Perk;
+use dbd_escape_chance_calculator::validation::Severity;
+use iced::Align;
+use iced::Button;
+use iced::Checkbox;
+use iced::Column;
+use iced::Container;
+use iced::PickList;
+use iced::Radio;
+use iced::Row;
+use iced::Scrollable;
+use iced::Space;
+use iced::Text;
+use iced::{Application, Clipboard, Command, Element, Length, Settings};
+
+/// The decimal places that can be picked for the escape chance
+const DECIMAL_OPTIONS: &[u8] = &[0, 1, 2, 3, 4, 6, 8, 12];
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    PatchSelected(Patch),
+    ChanceStyleSelected(ChanceStyle),
+    ChanceDecimalsSelected(u8),
+
+    CopyBuildCode,
+    LoadBuildCode,
+
+    ToggleExplanation,
+    CopyExplanation,
+
+    HookedSurvivorChange(u8),
+    AliveChange(u8, bool),
+
+    IncreaseSlipperyMeat,
+    DecreaseSlipperyMeat,
+
+    IncreaseUpTheAnte(u8),
+    DecreaseUpTheAnte(u8),
+
+    OfferingChange(u8, Option<Offering>),
+}
+
+/// The widget state for a single player's section
+#[derive(Default)]
+pub struct PlayerState {
+    up_the_ante_perk_picker_state: PerkPickerState,
+    offering_picker_state: OfferingPickerState,
+}
+
+pub struct App {
+    // assets: Assets,
+    escape_chance_settings: HookEscapeChanceSettings,
+    patch: Patch,
+
+    scrollable_state: iced::scrollable::State,
+    breakdown_scrollable_state: iced::scrollable::State,
+
+    patch_pick_list_state: iced::pick_list::State<Patch>,
+
+    preferences: Preferences,
+    preferences_status: Option<String>,
+    chance_style_pick_list_state: iced::pick_list::State<ChanceStyle>,
+    chance_decimals_pick_list_state: iced::pick_list::State<u8>,
+
+    copy_build_code_button_state: iced::button::State,
+    load_build_code_button_state: iced::button::State,
+    build_code_status: Option<String>,
+
+    show_explanation: bool,
+    toggle_explanation_button_state: iced::button::State,
+    copy_explanation_button_state: iced::button::State,
+
+    slippery_meat_perk_picker_state: PerkPickerState,
+    slippery_meat_perk_images: PerkImages,
+
+    player_states: [PlayerState; 4],
+    up_the_ante_perk_images: PerkImages,
+}
+
+impl Application for App {
+    type Executor = iced::executor::Default;
+    type Message = Message;
+    type Flags = Assets;
+
+    fn new(assets: Assets) -> (Self, Command<Message>) {
+        let slippery_meat_perk_images = assets.slippery_meat_perk_images;
+        let (preferences, preferences_status) = match Preferences::load() {
+            Ok(preferences) => (preferences, None),
+            Err(e) => (Preferences::default(), Some(e.to_string())),
+        };
+
+        (
+            App {
+                // assets,
+                escape_chance_settings: HookEscapeChanceSettings::new(),
+                patch: Patch::default(),
+
+                scrollable_state: iced::scrollable::State::new(),
+                breakdown_scrollable_state: iced::scrollable::State::new(),
+
+                patch_pick_list_state: iced::pick_list::State::default(),
+
+                preferences,
+                preferences_status,
+                chance_style_pick_list_state: iced::pick_list::State::default(),
+                chance_decimals_pick_list_state: iced::pick_list::State::default(),
+
+                copy_build_code_button_state: iced::button::State::new(),
+                load_build_code_button_state: iced::button::State::new(),
+                build_code_status: None,
+
+                show_explanation: false,
+                toggle_explanation_button_state: iced::button::State::new(),
+                copy_explanation_button_state: iced::button::State::new(),
+
+                slippery_meat_perk_picker_state: PerkPickerState::new(),
+                slippery_meat_perk_images,
+
+                player_states: Default::default(),
+                up_the_ante_perk_images: assets.up_the_ante_perk_images,
+            },
+            Command::none(),
+        )
+    }
+
+    fn title(&self) -> String {
+        String::from("DBD Escape Chance Calculator")
+    }
+
+    fn update(&mut self, message: Message, clipboard: &mut Clipboard) -> Command<Message> {
+        match message {
+            Message::PatchSelected(patch) => {
+                self.patch = patch;
+                Command::none()
+            }
+            Message::ChanceStyleSelected(style) => {
+                self.preferences.chance_format.style = style;
+                self.preferences_status = self.preferences.save().err().map(|e| e.to_string());
+                Command::none()
+            }
+            Message::ChanceDecimalsSelected(decimals) => {
+                self.preferences.chance_format.decimals = decimals;
+                self.preferences_status = self.preferences.save().err().map(|e| e.to_string());
+                Command::none()
+            }
+            Message::CopyBuildCode => {
+                let code = build_code::encode(&self.escape_chance_settings);
+                self.build_code_status = Some(format!("Copied {}", code));
+                clipboard.write(code);
+                Command::none()
+            }
+            Message::LoadBuildCode => {
+                self.build_code_status = match clipboard.read() {
+                    Some(code) => match build_code::decode(&code) {
+                        Ok(settings) => {
+                            self.escape_chance_settings = settings;
+                            Some(String::from("Loaded build code"))
+                        }
+                        Err(e) => Some(format!("Failed to load build code: {}", e)),
+                    },
+                    None => Some(String::from("The clipboard is empty")),
+                };
+                Command::none()
+            }
+            Message::ToggleExplanation => {
+                self.show_explanation = !self.show_explanation;
+                Command::none()
+            }
+            Message::CopyExplanation => {
+                let explanation =
+                    explanation::explain(&self.escape_chance_settings, self.patch.rule_set());
+                clipboard.write(explanation.to_markdown());
+                Command::none()
+            }
+            Message::HookedSurvivorChange(index) => {
+                self.escape_chance_settings.hooked_survivor = index;
+                Command::none()
+            }
+            Message::AliveChange(index, alive) => {
+                if let Some(survivor) = self
+                    .escape_chance_settings
+                    .survivors
+                    .get_mut(usize::from(index))
+                {
+                    survivor.alive = alive;
+                }
+
+                Command::none()
+            }
+            Message::IncreaseSlipperyMeat => {
+                self.escape_chance_settings.increment_slippery_meat_tier();
+                Command::none()
+            }
+            Message::DecreaseSlipperyMeat => {
+                self.escape_chance_settings.decrement_slippery_meat_tier();
+                Command::none()
+            }
+            Message::IncreaseUpTheAnte(index) => {
+                self.escape_chance_settings
+                    .increment_up_the_ante_tier(index);
+                Command::none()
+            }
+            Message::DecreaseUpTheAnte(index) => {
+                self.escape_chance_settings
+                    .decrement_up_the_ante_tier(index);
+                Command::none()
+            }
+            Message::OfferingChange(index, offering) => {
+                if let Some(survivor) = self
+                    .escape_chance_settings
+                    .survivors
+                    .get_mut(usize::from(index))
+                {
+                    survivor.offering = offering;
+                }
+
+                Command::none()
+            }
+        }
+    }
+
+    fn view(&mut self) -> Element<Message> {
+        let slippery_meat_column = Column::new()
+            .push(Text::new("Slippery Meat (Hooked Player)").size(20))
+            .push(PerkPicker::new(
+                &mut self.slippery_meat_perk_picker_state,
+                self.escape_chance_settings
+                    .hooked()
+                    .and_then(|survivor| survivor.perk_tier(Perk::SlipperyMeat)),
+                &self.slippery_meat_perk_images,
+                Message::DecreaseSlipperyMeat,
+                Message::IncreaseSlipperyMeat,
+            ))
+            .align_items(Align::Center)
+            .spacing(10);
+
+        let hooked_survivor = self.escape_chance_settings.hooked_survivor;
+        let up_the_ante_perk_images = &self.up_the_ante_perk_images;
+        let players_column = self
+            .player_states
+            .iter_mut()
+            .zip(self.escape_chance_settings.survivors.iter())
+            .enumerate()
+            .fold(Column::new(), |column, (index, (state, survivor))| {
+                let index = index as u8;
+                column.push(
+                    Column::new()
+                        .push(Text::new(format!("Player {}", index + 1)).size(20))
+                        .push(
+                            Row::new()
+                                .push(Radio::new(
+                                    index,
+                                    "Hooked",
+                                    Some(hooked_survivor),
+                                    Message::HookedSurvivorChange,
+                                ))
+                                .push(Checkbox::new(survivor.alive, "Alive", move |alive| {
+                                    Message::AliveChange(index, alive)
+                                }))
+                                .align_items(Align::Center)
+                                .spacing(20),
+                        )
+                        .push(Text::new("Up the Ante"))
+                        .push(PerkPicker::new(
+                            &mut state.up_the_ante_perk_picker_state,
+                            survivor.perk_tier(Perk::UpTheAnte),
+                            up_the_ante_perk_images,
+                            Message::DecreaseUpTheAnte(index),
+                            Message::IncreaseUpTheAnte(index),
+                        ))
+                        .push(Text::new("Offering"))
+                        .push(OfferingPicker::new(
+                            &mut state.offering_picker_state,
+                            survivor.offering,
+                            move |offering| Message::OfferingChange(index, offering),
+                        ))
+                        .align_items(Align::Center)
+                        .spacing(10),
+                )
+            })
+            .align_items(Align::Center)
+            .spacing(20);
+
+        let build_code_column = Column::new()
+            .push(
+                Row::new()
+                    .push(
+                        Button::new(
+                            &mut self.copy_build_code_button_state,
+                            Text::new("Copy code"),
+                        )
+                        .on_press(Message::CopyBuildCode),
+                    )
+                    .push(
+                        Button::new(
+                            &mut self.load_build_code_button_state,
+                            Text::new("Load code"),
+                        )
+                        .on_press(Message::LoadBuildCode),
+                    )
+                    .spacing(10),
+            )
+            .push(Text::new(
+                self.build_code_status.as_deref().unwrap_or_default(),
+            ))
+            .align_items(Align::Center)
+            .spacing(10);
+
+        let explanation_label = if self.show_explanation {
+            "Hide Explanation"
+        } else {
+            "Show Explanation"
+        };
+        let mut explanation_column = Column::new()
+            .push(
+                Row::new()
+                    .push(
+                        Button::new(
+                            &mut self.toggle_explanation_button_state,
+                            Text::new(explanation_label),
+                        )
+                        .on_press(Message::ToggleExplanation),
+                    )
+                    .push(
+                        Button::new(
+                            &mut self.copy_explanation_button_state,
+                            Text::new("Copy as Markdown"),
+                        )
+                        .on_press(Message::CopyExplanation),
+                    )
+                    .spacing(10),
+            )
+            .align_items(Align::Center)
+            .spacing(5);
+        if self.show_explanation {
+            let explanation =
+                explanation::explain(&self.escape_chance_settings, self.patch.rule_set());
+            explanation_column = explanation
+                .steps
+                .iter()
+                .enumerate()
+                .fold(explanation_column, |column, (index, step)| {
+                    column.push(Text::new(format!("{}. {}", index + 1, step)))
+                });
+        }
+
+        let body = Column::new()
+            .push(Text::new("Dead by Daylight").size(40))
+            .push(Text::new("Hook Escape Calculator").size(30))
+            .push(Space::new(Length::Shrink, Length::Units(10)))
+            .push(
+                Column::new()
+                    .push(Text::new("Patch").size(20))
+                    .push(PickList::new(
+                        &mut self.patch_pick_list_state,
+                        Patch::ALL,
+                        Some(self.patch),
+                        Message::PatchSelected,
+                    ))
+                    .align_items(Align::Center)
+                    .spacing(10),
+            )
+            .push(build_code_column)
+            .push(slippery_meat_column)
+            .push(players_column)
+            .push(explanation_column)
+            .align_items(Align::Center)
+            .width(Length::Fill)
+            .spacing(20);
+
+        let rules = self.patch.rule_set();
+        let chance_format = self.preferences.chance_format;
+        let total_escape_chance = match self.escape_chance_settings.try_calculate(rules) {
+            Ok(_) => format!(
+                "Total Escape Chance: {}",
+                chance_format.format(&Chance::of(&self.escape_chance_settings, rules))
+            ),
+            Err(_) => String::from("Total Escape Chance: invalid settings"),
+        };
+        let chance_format_row = Row::new()
+            .push(Text::new("Display"))
+            .push(PickList::new(
+                &mut self.chance_style_pick_list_state,
+                ChanceStyle::ALL,
+                Some(chance_format.style),
+                Message::ChanceStyleSelected,
+            ))
+            .push(Text::new("Decimals"))
+            .push(PickList::new(
+                &mut self.chance_decimals_pick_list_state,
+                DECIMAL_OPTIONS,
+                Some(chance_format.decimals),
+                Message::ChanceDecimalsSelected,
+            ))
+            .align_items(Align::Center)
+            .spacing(10);
+        let mut footer = Column::new()
+            .push(Text::new(total_escape_chance))
+            .push(chance_format_row)
+            .spacing(5);
+        if let Some(status) = self.preferences_status.as_deref() {
+            footer = footer.push(Text::new(format!("Preferences: {}", status)));
+        }
+        let footer =
+            self.escape_chance_settings
+                .validate()
+                .into_iter()
+                .fold(footer, |column, issue| {
+                    let (label, color) = match issue.severity() {
+                        Severity::Warning => ("Warning", iced::Color::from_rgb8(0xFF, 0xD7, 0x00)),
+                        Severity::Error => ("Error", iced::Color::from_rgb8(0xFF, 0x45, 0x45)),
+                    };
+                    column.push(Text::new(format!("{}: {}", label, issue)).color(color))
+                });
+        let base_chance = Chance::of(&self.escape_chance_settings, rules);
+        let breakdown = sensitivity::analyze(&self.escape_chance_settings, rules)
+            .into_iter()
+            .fold(
+                Scrollable::new(&mut self.breakdown_scrollable_state)
+                    .width(Length::Fill)
+                    .max_height(120),
+                |scrollable, sensitivity| {
+                    scrollable.push(Text::new(format!(
+                        "{}: {}",
+                        sensitivity.adjustment,
+                        chance_format.format_change(&base_chance, &sensitivity.chance)
+                    )))
+                },
+            );
+        let footer = footer.push(Text::new("Breakdown").size(20)).push(breakdown);
+
+        Container::new(
+            Column::new()
+                .push(
+                    Scrollable::new(&mut self.scrollable_state)
+                        .push(body)
+                        .padding(20)
+                        .width(Length::Fill)
+                        .height(Length::Fill),
+                )
+                .push(
+                    Container::new(footer)
+                        .padding(20)
+                        .style(ContainerForegroundStyle)
+                        .width(Length::Fill),
+                ),
+        )
+        .style(ContainerBackgroundStyle)
+        .into()
+    }
+}
+
+/// Open the GUI.
+pub fn run() -> anyhow::Result<()> {
+    let assets = Assets::new().context("failed to load assets")?;
+    let mut settings = Settings::with_flags(assets);
+    settings.window.size = (640, 480);
+    App::run(settings).context("failed to run app")?;
+
+    Ok(())
+}
+
+pub struct ContainerBackgroundStyle;
+
+impl iced::container::StyleSheet for ContainerBackgroundStyle {
+    fn style(&self) -> iced::container::Style {
+        iced::container::Style {
+            background: iced::Color::BLACK.into(), // 0x3F, 0x3F, 0x3F
+            text_color: iced::Color::WHITE.into(), // iced::Color::from_rgb8(0xFF, 0x00, 0x00).into(),
+            ..iced::container::Style::default()
+        }
+    }
+}
+
+pub struct ContainerForegroundStyle;
+
+impl iced::container::StyleSheet for ContainerForegroundStyle {
+    fn style(&self) -> iced::container::Style {
+        iced::container::Style {
+            background: iced::Color::from_rgb8(0x31, 0x36, 0x38).into(),
+            text_color: iced::Color::WHITE.into(), // iced::Color::from_rgb8(0xFF, 0x00, 0x00).into(),
+            ..iced::container::Style::default()
+        }
+    }
+}
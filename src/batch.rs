@@ -0,0 +1,294 @@
+//! Evaluate a CSV file of scenarios.
+//!
+//! Each row describes one scenario with these columns, all optional:
+//! * `slippery_meat`: The Slippery Meat tier of the hooked survivor, like `III`
+//! * `up_the_ante`: Comma-separated Up the Ante tiers of the alive survivors, like `"III,II"`
+//! * `salty_lips`: The # of Vigo's Jars of Salty Lips burned
+//! * `alive`: The # of alive survivors, including the hooked survivor
+//!
+//! Rows are written back with the columns in [`OUTPUT_COLUMNS`] appended.
+//! Rows with the same # of fields as the header are written back unchanged.
+//! Rows with fewer fields are padded with empty fields, and rows with more lose the fields past the header.
+//! Rows that aren't valid UTF-8 lose every input field, which are written empty.
+//! All of these are reported as errors.
+//! Chances are written with [`ChanceFormat::format_probability`].
+//! Other columns are passed through, so rows can carry labels.
+
+use crate::chance_format::Chance;
+use crate::chance_format::ChanceFormat;
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::hook_escape_chance_settings::TierList;
+use crate::rule_set::RuleSet;
+use csv::StringRecord;
+use std::fmt::Display;
+use std::io::Read;
+use std::io::Write;
+
+/// The columns appended to each row
+pub const OUTPUT_COLUMNS: &[&str] = &[
+    "escape_chance",
+    "escape_chance_per_attempt",
+    "num_tries",
+    "error",
+];
+
+/// A row that could not be evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    /// The line the row starts on, starting from 1
+    pub line: u64,
+
+    /// What was wrong with the row
+    pub message: String,
+}
+
+impl Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// The column indexes of the scenario fields
+struct Columns {
+    slippery_meat: Option<usize>,
+    up_the_ante: Option<usize>,
+    salty_lips: Option<usize>,
+    alive: Option<usize>,
+}
+
+impl Columns {
+    fn new(headers: &StringRecord) -> Self {
+        let find = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header.trim().eq_ignore_ascii_case(name))
+        };
+
+        Self {
+            slippery_meat: find("slippery_meat"),
+            up_the_ante: find("up_the_ante"),
+            salty_lips: find("salty_lips"),
+            alive: find("alive"),
+        }
+    }
+
+    /// Parse the scenario in a row.
+    fn parse(&self, record: &StringRecord) -> Result<HookEscapeChanceSettings, String> {
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .map(str::trim)
+                .filter(|field| !field.is_empty())
+        };
+
+        let slippery_meat = field(self.slippery_meat)
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| format!("invalid slippery_meat: {}", e))?;
+        let up_the_ante: TierList = field(self.up_the_ante)
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| format!("invalid up_the_ante: {}", e))?
+            .unwrap_or_default();
+        let salty_lips = field(self.salty_lips)
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| format!("invalid salty_lips: {}", e))?
+            .unwrap_or(0);
+        let alive = field(self.alive)
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| format!("invalid alive: {}", e))?
+            .unwrap_or(4);
+
+        HookEscapeChanceSettings::from_counts(slippery_meat, &up_the_ante.0, salty_lips, alive)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Evaluate every scenario in a CSV file, writing the results as CSV.
+///
+/// Rows that can't be evaluated are still written, with only the `error` column filled,
+/// and are returned so they can be reported.
+/// Rows with more fields than the header are written without the extra fields,
+/// and rows that aren't valid UTF-8 are written with every other column empty.
+/// Both are returned as well.
+/// This only fails if the input can't be read or the output can't be written.
+pub fn evaluate<R, W>(
+    reader: R,
+    writer: W,
+    rules: &RuleSet,
+    format: &ChanceFormat,
+) -> Result<Vec<RowError>, csv::Error>
+where
+    R: Read,
+    W: Write,
+{
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let mut writer = csv::Writer::from_writer(writer);
+
+    let headers = reader.headers()?.clone();
+    let columns = Columns::new(&headers);
+    let num_fields = headers.len();
+    let mut output_headers = headers;
+    output_headers.extend(OUTPUT_COLUMNS);
+    writer.write_record(&output_headers)?;
+
+    let mut errors = Vec::new();
+    for record in reader.records() {
+        let mut record = match record {
+            Ok(record) => record,
+            // A row that isn't valid UTF-8 is consumed, so the rest of the file can still be read
+            Err(e) if matches!(e.kind(), csv::ErrorKind::Utf8 { .. }) => {
+                let line = e.position().map_or(0, |position| position.line());
+                let message = e.to_string();
+
+                let mut record = StringRecord::new();
+                for _ in 0..num_fields + OUTPUT_COLUMNS.len() - 1 {
+                    record.push_field("");
+                }
+                record.push_field(&message);
+                writer.write_record(&record)?;
+
+                errors.push(RowError { line, message });
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        let line = record.position().map_or(0, |position| position.line());
+
+        // Keep the appended columns aligned with the header
+        let num_record_fields = record.len();
+        record.truncate(num_fields);
+        while record.len() < num_fields {
+            record.push_field("");
+        }
+
+        let result = if num_record_fields != num_fields {
+            Err(format!(
+                "expected {} fields, found {}",
+                num_fields, num_record_fields
+            ))
+        } else {
+            columns.parse(&record)
+        };
+        match result.and_then(|settings| {
+            settings
+                .try_calculate(rules)
+                .map(|_| Chance::of(&settings, rules))
+                .map_err(|e| e.to_string())
+        }) {
+            Ok(chance) => {
+                record.push_field(&format.format_probability(&chance.escape_chance));
+                record.push_field(&format.format_probability(&chance.escape_chance_per_attempt));
+                record.push_field(&chance.num_tries.to_string());
+                record.push_field("");
+            }
+            Err(message) => {
+                record.push_field("");
+                record.push_field("");
+                record.push_field("");
+                record.push_field(&message);
+                errors.push(RowError { line, message });
+            }
+        }
+
+        writer.write_record(&record)?;
+    }
+
+    writer.flush()?;
+
+    Ok(errors)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chance_format::ChanceStyle;
+
+    #[test]
+    fn evaluate_rows() {
+        let input = "\
+name,slippery_meat,up_the_ante,salty_lips,alive
+default,,,,
+best,III,\"III,III,III\",4,4
+bad tier,IV,,,
+too many,,,5,
+dead survivors,,\"III,III,III\",,2
+short,III
+long,III,,,4,extra
+";
+        let mut output = Vec::new();
+        let errors = evaluate(
+            input.as_bytes(),
+            &mut output,
+            &RuleSet::LIVE,
+            &ChanceFormat::default(),
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "name,slippery_meat,up_the_ante,salty_lips,alive,escape_chance,escape_chance_per_attempt,num_tries,error"
+        );
+        assert_eq!(lines[1], "default,,,,,11.53%,4.00%,3,");
+        assert!(lines[2].starts_with("best,III,\"III,III,III\",4,4,"));
+        assert!(lines[3].starts_with("bad tier,IV,,,,,,,"));
+        assert_eq!(
+            lines[5],
+            "dead survivors,,\"III,III,III\",,2,,,,\"invalid settings: 3 copies of Up the Ante requested, but there are only 2 alive survivors\""
+        );
+        assert_eq!(lines[6], "short,III,,,,,,,\"expected 5 fields, found 2\"");
+        assert_eq!(lines[7], "long,III,,,4,,,,\"expected 5 fields, found 6\"");
+        assert_eq!(lines.len(), 8);
+
+        assert_eq!(errors.len(), 5);
+        assert_eq!(errors[0].line, 4);
+        assert!(errors[0].message.contains("invalid slippery_meat"));
+        assert_eq!(errors[1].line, 5);
+        assert_eq!(errors[2].line, 6);
+        assert_eq!(errors[3].line, 7);
+        assert_eq!(errors[4].line, 8);
+
+        let mut output = Vec::new();
+        let format = ChanceFormat {
+            style: ChanceStyle::Fraction,
+            decimals: 2,
+        };
+        evaluate(
+            "alive\n4\n".as_bytes(),
+            &mut output,
+            &RuleSet::LIVE,
+            &format,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().nth(1).unwrap(), "4,1801/15625,1/25,3,");
+    }
+
+    #[test]
+    fn evaluate_invalid_utf8_row() {
+        let input = b"name,alive\nfirst,4\nbad\xff,4\nlast,4\n";
+        let mut output = Vec::new();
+        let errors = evaluate(
+            &input[..],
+            &mut output,
+            &RuleSet::LIVE,
+            &ChanceFormat::default(),
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], "first,4,11.53%,4.00%,3,");
+        assert!(lines[2].starts_with(",,,,,"));
+        assert!(lines[2].len() > ",,,,,".len());
+        assert_eq!(lines[3], "last,4,11.53%,4.00%,3,");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 3);
+    }
+}
@@ -0,0 +1,214 @@
+//! Short, shareable codes for [`HookEscapeChanceSettings`].
+//!
+//! A code is url-safe base64 of the following bytes:
+//! 1. The code version
+//! 2. The hooked survivor index
+//! 3. One byte per survivor: bit 7 is alive, bits 4-6 are the offering,
+//!    bits 2-3 are the Up the Ante tier and bits 0-1 are the Slippery Meat tier.
+//!    Tiers are 0 for none, then 1 to 3. Offerings are 0 for none, then 1 to 6.
+//! 4. A big-endian Fletcher-16 checksum of all previous bytes
+//!
+//! Only the first copy of each perk is kept, and perk slot order is not preserved.
+
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::hook_escape_chance_settings::Tier;
+use crate::offering::Offering;
+use crate::survivor::Perk;
+use crate::survivor::Survivor;
+use std::fmt::Display;
+
+/// The build code version written by this version of the calculator
+pub const BUILD_CODE_VERSION: u8 = 1;
+
+const NUM_BYTES: usize = 2 + 4 + 2;
+
+/// An error that occured while decoding a build code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The code is not valid base64
+    InvalidBase64,
+
+    /// The code decoded to the wrong # of bytes
+    InvalidLength(usize),
+
+    /// The checksum did not match, so the code was likely mistyped
+    ChecksumMismatch,
+
+    /// The code version is not supported
+    UnsupportedVersion(u8),
+
+    /// A field had a value that does not map to anything
+    InvalidValue { field: &'static str, value: u8 },
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidBase64 => "build code is not valid base64".fmt(f),
+            Self::InvalidLength(len) => {
+                write!(f, "build code has {} bytes, expected {}", len, NUM_BYTES)
+            }
+            Self::ChecksumMismatch => {
+                "build code checksum mismatch, was it copied correctly?".fmt(f)
+            }
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported build code version {}, the latest is {}",
+                version, BUILD_CODE_VERSION
+            ),
+            Self::InvalidValue { field, value } => {
+                write!(f, "invalid {} value {} in build code", field, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Encode settings as a build code.
+pub fn encode(settings: &HookEscapeChanceSettings) -> String {
+    let mut bytes = Vec::with_capacity(NUM_BYTES);
+    bytes.push(BUILD_CODE_VERSION);
+    bytes.push(settings.hooked_survivor);
+    bytes.extend(settings.survivors.iter().map(encode_survivor));
+
+    let checksum = fletcher16(&bytes);
+    bytes.extend_from_slice(&checksum.to_be_bytes());
+
+    base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)
+}
+
+/// Decode a build code into settings.
+///
+/// Whitespace around the code is ignored.
+pub fn decode(code: &str) -> Result<HookEscapeChanceSettings, DecodeError> {
+    let bytes = base64::decode_config(code.trim(), base64::URL_SAFE_NO_PAD)
+        .map_err(|_| DecodeError::InvalidBase64)?;
+    if bytes.len() != NUM_BYTES {
+        return Err(DecodeError::InvalidLength(bytes.len()));
+    }
+
+    let (data, checksum) = bytes.split_at(NUM_BYTES - 2);
+    if fletcher16(data).to_be_bytes() != checksum {
+        return Err(DecodeError::ChecksumMismatch);
+    }
+
+    if data[0] != BUILD_CODE_VERSION {
+        return Err(DecodeError::UnsupportedVersion(data[0]));
+    }
+
+    let mut settings = HookEscapeChanceSettings::new();
+    settings.hooked_survivor = data[1];
+    if settings.hooked().is_none() {
+        return Err(DecodeError::InvalidValue {
+            field: "hooked survivor",
+            value: data[1],
+        });
+    }
+    for (survivor, byte) in settings.survivors.iter_mut().zip(data[2..].iter()) {
+        *survivor = decode_survivor(*byte)?;
+    }
+
+    Ok(settings)
+}
+
+fn encode_survivor(survivor: &Survivor) -> u8 {
+    let alive = u8::from(survivor.alive) << 7;
+    let offering = survivor.offering.map_or(0, encode_offering) << 4;
+    let up_the_ante = encode_tier(survivor.perk_tier(Perk::UpTheAnte)) << 2;
+    let slippery_meat = encode_tier(survivor.perk_tier(Perk::SlipperyMeat));
+
+    alive | offering | up_the_ante | slippery_meat
+}
+
+fn decode_survivor(byte: u8) -> Result<Survivor, DecodeError> {
+    let mut survivor = Survivor::new();
+    survivor.alive = byte & 0x80 != 0;
+    survivor.offering = decode_offering((byte >> 4) & 0x07)?;
+    survivor.set_perk_tier(Perk::UpTheAnte, decode_tier((byte >> 2) & 0x03));
+    survivor.set_perk_tier(Perk::SlipperyMeat, decode_tier(byte & 0x03));
+
+    Ok(survivor)
+}
+
+fn encode_tier(tier: Option<Tier>) -> u8 {
+    tier.map_or(0, |tier| tier.index() as u8 + 1)
+}
+
+fn decode_tier(value: u8) -> Option<Tier> {
+    match value {
+        1 => Some(Tier::I),
+        2 => Some(Tier::II),
+        3 => Some(Tier::III),
+        _ => None,
+    }
+}
+
+fn encode_offering(offering: Offering) -> u8 {
+    Offering::ALL
+        .iter()
+        .position(|o| *o == offering)
+        .map_or(0, |index| index as u8 + 1)
+}
+
+fn decode_offering(value: u8) -> Result<Option<Offering>, DecodeError> {
+    match value {
+        0 => Ok(None),
+        value => Offering::ALL
+            .get(usize::from(value) - 1)
+            .copied()
+            .map(Some)
+            .ok_or(DecodeError::InvalidValue {
+                field: "offering",
+                value,
+            }),
+    }
+}
+
+/// Fletcher-16 checksum
+fn fletcher16(data: &[u8]) -> u16 {
+    let (sum1, sum2) = data.iter().fold((0u16, 0u16), |(sum1, sum2), byte| {
+        let sum1 = (sum1 + u16::from(*byte)) % 255;
+        let sum2 = (sum2 + sum1) % 255;
+        (sum1, sum2)
+    });
+
+    (sum2 << 8) | sum1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.hooked_survivor = 2;
+        settings.survivors[2].set_perk_tier(Perk::SlipperyMeat, Some(Tier::III));
+        settings.survivors[2].offering = Some(Offering::IvoryChalkPouch);
+        settings.survivors[0].set_perk_tier(Perk::UpTheAnte, Some(Tier::II));
+        settings.survivors[1].offering = Some(Offering::VigosJarOfSaltyLips);
+        settings.survivors[3].alive = false;
+
+        let code = encode(&settings);
+        assert_eq!(code.len(), 11);
+        assert_eq!(decode(&code), Ok(settings));
+        assert_eq!(
+            decode(&encode(&HookEscapeChanceSettings::new())),
+            Ok(HookEscapeChanceSettings::new())
+        );
+    }
+
+    #[test]
+    fn bad_codes() {
+        let code = encode(&HookEscapeChanceSettings::new());
+
+        let mut typo = code.into_bytes();
+        typo[4] = if typo[4] == b'A' { b'B' } else { b'A' };
+        let typo = String::from_utf8(typo).unwrap();
+        assert_eq!(decode(&typo), Err(DecodeError::ChecksumMismatch));
+
+        assert_eq!(decode("!!!"), Err(DecodeError::InvalidBase64));
+        assert_eq!(decode("AAAA"), Err(DecodeError::InvalidLength(3)));
+    }
+}
@@ -0,0 +1,349 @@
+//! Formatting escape chances for display.
+//!
+//! All styles work on exact fractions, so results never show float noise like `11.526400000000001%`.
+
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::rule_set::RuleSet;
+use num::BigInt;
+use num::BigRational;
+use num::One;
+use num::Signed;
+use num::Zero;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// An escape chance to format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chance {
+    /// The chance of escaping on any attempt
+    pub escape_chance: BigRational,
+
+    /// The chance of escaping on a single attempt
+    pub escape_chance_per_attempt: BigRational,
+
+    /// The # of self-unhook attempts
+    pub num_tries: u8,
+}
+
+impl Chance {
+    /// Calculate the exact chance for the given settings.
+    ///
+    /// This does not check the settings for problems.
+    pub fn of(settings: &HookEscapeChanceSettings, rules: &RuleSet) -> Self {
+        let (escape_chance_per_attempt, num_tries) = settings.exact_distribution(rules);
+
+        Self {
+            escape_chance: settings.calculate_exact(rules),
+            escape_chance_per_attempt,
+            num_tries,
+        }
+    }
+}
+
+/// A way to display a chance
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChanceStyle {
+    /// `11.53%`
+    Percent,
+
+    /// `1 in 8.68`
+    OneIn,
+
+    /// `1801/15625`
+    Fraction,
+
+    /// `4.00% per attempt, 3 attempts`
+    PerAttempt,
+}
+
+impl ChanceStyle {
+    /// All styles
+    pub const ALL: &'static [ChanceStyle] = &[
+        ChanceStyle::Percent,
+        ChanceStyle::OneIn,
+        ChanceStyle::Fraction,
+        ChanceStyle::PerAttempt,
+    ];
+
+    /// The name used on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Percent => "percent",
+            Self::OneIn => "one-in",
+            Self::Fraction => "fraction",
+            Self::PerAttempt => "per-attempt",
+        }
+    }
+}
+
+impl Display for ChanceStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Percent => "Percent".fmt(f),
+            Self::OneIn => "1 in N".fmt(f),
+            Self::Fraction => "Fraction".fmt(f),
+            Self::PerAttempt => "Per Attempt".fmt(f),
+        }
+    }
+}
+
+impl FromStr for ChanceStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|style| style.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "invalid style `{}`, expected percent, one-in, fraction or per-attempt",
+                    s
+                )
+            })
+    }
+}
+
+/// How to format a chance
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChanceFormat {
+    /// The style to use
+    pub style: ChanceStyle,
+
+    /// The # of decimal places, for styles that round
+    pub decimals: u8,
+}
+
+impl ChanceFormat {
+    /// The most decimal places allowed
+    pub const MAX_DECIMALS: u8 = 12;
+
+    /// Percent with the given # of decimal places
+    pub fn percent(decimals: u8) -> Self {
+        Self {
+            style: ChanceStyle::Percent,
+            decimals,
+        }
+    }
+
+    /// Format a chance in this style.
+    pub fn format(&self, chance: &Chance) -> String {
+        match self.style {
+            ChanceStyle::PerAttempt => format!(
+                "{} per attempt, {} attempts",
+                self.format_probability(&chance.escape_chance_per_attempt),
+                chance.num_tries
+            ),
+            _ => self.format_probability(&chance.escape_chance),
+        }
+    }
+
+    /// Format a single probability in this style, like one column of a table.
+    ///
+    /// The per-attempt style has no single number, so it uses a percent.
+    pub fn format_probability(&self, value: &BigRational) -> String {
+        let decimals = self.decimals.min(Self::MAX_DECIMALS);
+
+        match self.style {
+            ChanceStyle::Percent | ChanceStyle::PerAttempt => format_percent(value, decimals),
+            ChanceStyle::OneIn => format_one_in(value, decimals),
+            ChanceStyle::Fraction => value.to_string(),
+        }
+    }
+
+    /// Format the change from one chance to another in this style, like `+2.34%`.
+    ///
+    /// `1 in N` odds can't be subtracted, so that style shows both chances instead.
+    pub fn format_change(&self, before: &Chance, after: &Chance) -> String {
+        let decimals = self.decimals.min(Self::MAX_DECIMALS);
+        let delta = &after.escape_chance - &before.escape_chance;
+
+        match self.style {
+            ChanceStyle::Percent => format_percent_change(&delta, decimals),
+            ChanceStyle::OneIn => format!(
+                "{} -> {}",
+                format_one_in(&before.escape_chance, decimals),
+                format_one_in(&after.escape_chance, decimals)
+            ),
+            ChanceStyle::Fraction => format!("{}{}", sign(&delta), delta),
+            ChanceStyle::PerAttempt => {
+                let delta = &after.escape_chance_per_attempt - &before.escape_chance_per_attempt;
+                let num_tries = i16::from(after.num_tries) - i16::from(before.num_tries);
+                format!(
+                    "{} per attempt, {:+} attempts",
+                    format_percent_change(&delta, decimals),
+                    num_tries
+                )
+            }
+        }
+    }
+}
+
+/// The sign to put in front of a change, which is part of the number if it is negative.
+/// Format a change as a signed percent, like `+2.34%`.
+///
+/// The sign comes from the rounded value, so changes that round to zero are `+0.00%`.
+fn format_percent_change(delta: &BigRational, decimals: u8) -> String {
+    let percent = format_percent(delta, decimals);
+    if percent.starts_with('-') {
+        percent
+    } else {
+        format!("+{}", percent)
+    }
+}
+
+fn sign(value: &BigRational) -> &'static str {
+    if value.is_negative() {
+        ""
+    } else {
+        "+"
+    }
+}
+
+impl Default for ChanceFormat {
+    fn default() -> Self {
+        Self::percent(2)
+    }
+}
+
+/// Round a value to the given # of decimal places, without float error.
+pub fn format_decimal(value: &BigRational, decimals: u8) -> String {
+    let scale = num::pow(BigInt::from(10), usize::from(decimals));
+    let scaled = (value * BigRational::from_integer(scale.clone()))
+        .round()
+        .to_integer();
+
+    let sign = if scaled.is_negative() { "-" } else { "" };
+    let scaled = scaled.abs();
+    let integer = &scaled / &scale;
+    if decimals == 0 {
+        return format!("{}{}", sign, integer);
+    }
+
+    let fraction = &scaled % &scale;
+    format!(
+        "{}{}.{:0>width$}",
+        sign,
+        integer,
+        fraction,
+        width = usize::from(decimals)
+    )
+}
+
+/// Format a probability as a percent, like `11.53%`.
+pub fn format_percent(value: &BigRational, decimals: u8) -> String {
+    format!(
+        "{}%",
+        format_decimal(&(value * BigRational::from_integer(100.into())), decimals)
+    )
+}
+
+/// Format a probability as odds, like `1 in 8.68`.
+pub fn format_one_in(value: &BigRational, decimals: u8) -> String {
+    if value.is_zero() {
+        return String::from("never");
+    }
+    if *value >= BigRational::one() {
+        return String::from("always");
+    }
+
+    format!("1 in {}", format_decimal(&value.recip(), decimals))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hook_escape_chance_settings::Tier;
+    use crate::survivor::Perk;
+
+    #[test]
+    fn styles() {
+        let chance = Chance::of(&HookEscapeChanceSettings::new(), &RuleSet::LIVE);
+        let format = |style, decimals| ChanceFormat { style, decimals }.format(&chance);
+
+        assert_eq!(format(ChanceStyle::Percent, 2), "11.53%");
+        assert_eq!(format(ChanceStyle::Percent, 6), "11.526400%");
+        assert_eq!(format(ChanceStyle::Percent, 0), "12%");
+        assert_eq!(format(ChanceStyle::OneIn, 2), "1 in 8.68");
+        assert_eq!(format(ChanceStyle::Fraction, 2), "1801/15625");
+        assert_eq!(
+            format(ChanceStyle::PerAttempt, 1),
+            "4.0% per attempt, 3 attempts"
+        );
+
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, Some(Tier::I));
+        let chance = Chance::of(&settings, &RuleSet::LIVE);
+        assert_eq!(
+            ChanceFormat::percent(12).format(&chance),
+            "31.013021894400%"
+        );
+    }
+
+    #[test]
+    fn changes() {
+        let before = Chance::of(&HookEscapeChanceSettings::new(), &RuleSet::LIVE);
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, Some(Tier::I));
+        let after = Chance::of(&settings, &RuleSet::LIVE);
+        let format = |style, decimals| ChanceFormat { style, decimals };
+
+        assert_eq!(
+            format(ChanceStyle::Percent, 2).format_change(&before, &after),
+            "+19.49%"
+        );
+        assert_eq!(
+            format(ChanceStyle::Percent, 2).format_change(&after, &before),
+            "-19.49%"
+        );
+        assert_eq!(
+            format(ChanceStyle::Percent, 2).format_change(&before, &before),
+            "+0.00%"
+        );
+        let tiny = Chance {
+            escape_chance: &before.escape_chance - BigRational::new(1.into(), 1_000_000.into()),
+            ..before.clone()
+        };
+        assert_eq!(
+            format(ChanceStyle::Percent, 2).format_change(&before, &tiny),
+            "+0.00%"
+        );
+        assert_eq!(
+            format(ChanceStyle::Percent, 4).format_change(&before, &tiny),
+            "-0.0001%"
+        );
+        assert_eq!(
+            format(ChanceStyle::OneIn, 2).format_change(&before, &after),
+            "1 in 8.68 -> 1 in 3.22"
+        );
+        assert_eq!(
+            format(ChanceStyle::PerAttempt, 0).format_change(&after, &before),
+            "-2% per attempt, -3 attempts"
+        );
+        assert!(format(ChanceStyle::Fraction, 2)
+            .format_change(&before, &after)
+            .starts_with('+'));
+    }
+
+    #[test]
+    fn decimal() {
+        let value = BigRational::new(5.into(), 1000.into());
+        assert_eq!(format_decimal(&value, 2), "0.01");
+        assert_eq!(format_decimal(&-value, 2), "-0.01");
+        assert_eq!(format_decimal(&BigRational::zero(), 3), "0.000");
+        assert_eq!(format_one_in(&BigRational::zero(), 2), "never");
+    }
+
+    #[test]
+    fn parse_style() {
+        for style in ChanceStyle::ALL.iter().copied() {
+            assert_eq!(style.name().parse(), Ok(style));
+        }
+        assert!("odds".parse::<ChanceStyle>().is_err());
+    }
+}
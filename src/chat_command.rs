@@ -0,0 +1,327 @@
+//! A compact scenario grammar for chat bots, like `!kobe sm3 uta3x2 lips2 alive3`.
+//!
+//! Words are separated by whitespace and are case-insensitive:
+//! * `sm<tier>`: Slippery Meat on the hooked survivor, like `sm3` or `smIII`
+//! * `uta<tier>` or `uta<tier>x<count>`: Up the Ante on alive survivors, like `uta2` or `uta3x2`. The count after `x` can't be empty and must be at least 1
+//! * `lips` or `lips<count>`: The # of Vigo's Jars of Salty Lips burned, like `lips2`
+//! * `alive<count>`: The # of alive survivors, including the hooked survivor
+//! * `live` or `original`: The patch to use the rules of
+//!
+//! Tiers are Roman or Arabic numerals. A leading `!command` word is ignored,
+//! so bots can pass the whole chat message.
+
+use crate::chance_format::format_percent;
+use crate::chance_format::Chance;
+use crate::chance_format::ChanceFormat;
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::hook_escape_chance_settings::Tier;
+use crate::rule_set::Patch;
+use crate::survivor::Perk;
+use crate::validation::ValidationError;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The words that are understood, for error messages
+const EXPECTED: &str = "sm<tier>, uta<tier>x<count>, lips<count>, alive<count>, live or original";
+
+/// An error for a chat command that can't be answered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A word is not part of the grammar
+    UnknownWord(String),
+
+    /// A word has a tier that is not I, II or III
+    InvalidTier(String),
+
+    /// A word has a count that is not a number
+    InvalidCount(String),
+
+    /// Something was given more than once
+    Duplicate(String),
+
+    /// The scenario is not a legal trial
+    Validation(ValidationError),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownWord(word) => write!(f, "unknown `{}`, try {}", word, EXPECTED),
+            Self::InvalidTier(word) => {
+                write!(f, "invalid tier in `{}`, expected 1-3 or I-III", word)
+            }
+            Self::InvalidCount(word) => write!(f, "invalid count in `{}`", word),
+            Self::Duplicate(word) => write!(f, "`{}` was given more than once", word),
+            Self::Validation(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ValidationError> for ParseError {
+    fn from(e: ValidationError) -> Self {
+        Self::Validation(e)
+    }
+}
+
+/// A parsed chat command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatQuery {
+    /// The Slippery Meat tier of the hooked survivor
+    pub slippery_meat: Option<Tier>,
+
+    /// The Up the Ante tiers of the alive survivors
+    pub up_the_ante: Vec<Tier>,
+
+    /// The # of Vigo's Jars of Salty Lips burned
+    pub salty_lips: u8,
+
+    /// The # of alive survivors, including the hooked survivor
+    pub alive: u8,
+
+    /// The patch to use the rules of
+    pub patch: Patch,
+
+    /// The scenario, made from the fields above
+    pub settings: HookEscapeChanceSettings,
+}
+
+impl ChatQuery {
+    /// A one-line answer, like `Slippery Meat III, 3 alive: 39.36% to escape (8% per attempt, 6 attempts)`.
+    ///
+    /// Warnings about parts of the loadout that have no effect are appended, like in the GUI.
+    pub fn reply(&self) -> String {
+        let rules = self.patch.rule_set();
+        let chance = Chance::of(&self.settings, rules);
+
+        let mut loadout = Vec::new();
+        if let Some(tier) = self.slippery_meat {
+            loadout.push(format!("{} {}", Perk::SlipperyMeat, tier));
+        }
+        for tier in [Tier::III, Tier::II, Tier::I].iter() {
+            match self.up_the_ante.iter().filter(|t| *t == tier).count() {
+                0 => {}
+                1 => loadout.push(format!("{} {}", Perk::UpTheAnte, tier)),
+                count => loadout.push(format!("{} {} x{}", Perk::UpTheAnte, tier, count)),
+            }
+        }
+        if self.salty_lips != 0 {
+            loadout.push(format!("{} Salty Lips", self.salty_lips));
+        }
+        if loadout.is_empty() {
+            loadout.push("No perks or offerings".to_string());
+        }
+
+        let mut reply = format!("{}, {} alive", loadout.join(" + "), self.alive);
+        if self.patch != Patch::Live {
+            reply.push_str(&format!(" ({})", self.patch));
+        }
+        reply.push_str(&format!(
+            ": {} to escape ({} per attempt, {} attempts)",
+            ChanceFormat::default().format(&chance),
+            format_percent(&chance.escape_chance_per_attempt, 0),
+            chance.num_tries
+        ));
+        for issue in self.settings.validate() {
+            reply.push_str(&format!(". Warning: {}", issue));
+        }
+
+        reply
+    }
+}
+
+/// Parse the tier at the end of a word.
+fn parse_tier(word: &str, rest: &str) -> Result<Tier, ParseError> {
+    rest.parse()
+        .map_err(|_| ParseError::InvalidTier(word.to_string()))
+}
+
+/// Parse the count at the end of a word, or 1 if there is none.
+fn parse_count(word: &str, rest: &str) -> Result<u8, ParseError> {
+    if rest.is_empty() {
+        return Ok(1);
+    }
+
+    rest.parse()
+        .map_err(|_| ParseError::InvalidCount(word.to_string()))
+}
+
+/// Set a value that may only be given once.
+fn set_once<T>(slot: &mut Option<T>, value: T, word: &str) -> Result<(), ParseError> {
+    if slot.is_some() {
+        return Err(ParseError::Duplicate(word.to_string()));
+    }
+    *slot = Some(value);
+
+    Ok(())
+}
+
+impl FromStr for ChatQuery {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut slippery_meat = None;
+        let mut up_the_ante = Vec::new();
+        let mut salty_lips = None;
+        let mut alive = None;
+        let mut patch = None;
+
+        let mut words = s.split_whitespace().peekable();
+        if words.peek().is_some_and(|word| word.starts_with('!')) {
+            words.next();
+        }
+
+        for word in words {
+            let lower = word.to_ascii_lowercase();
+            if let Some(rest) = lower.strip_prefix("alive") {
+                let count = rest
+                    .parse()
+                    .map_err(|_| ParseError::InvalidCount(word.to_string()))?;
+                set_once(&mut alive, count, "alive")?;
+            } else if let Ok(value) = lower.parse::<Patch>() {
+                set_once(&mut patch, value, "patch")?;
+            } else if let Some(rest) = lower.strip_prefix("sm") {
+                set_once(&mut slippery_meat, parse_tier(word, rest)?, "sm")?;
+            } else if let Some(rest) = lower.strip_prefix("uta") {
+                let (tier, count) = match rest.split_once('x') {
+                    Some((tier, count)) => (tier, Some(count)),
+                    None => (rest, None),
+                };
+                let tier = parse_tier(word, tier)?;
+                // A count after `x` is required, so `uta3x` isn't silently one copy
+                let count: u8 = match count {
+                    Some(count) => count
+                        .parse()
+                        .map_err(|_| ParseError::InvalidCount(word.to_string()))?,
+                    None => 1,
+                };
+                if count == 0 {
+                    return Err(ParseError::InvalidCount(word.to_string()));
+                }
+                up_the_ante.extend(vec![tier; usize::from(count)]);
+            } else if let Some(rest) = lower.strip_prefix("lips") {
+                set_once(&mut salty_lips, parse_count(word, rest)?, "lips")?;
+            } else {
+                return Err(ParseError::UnknownWord(word.to_string()));
+            }
+        }
+
+        let salty_lips = salty_lips.unwrap_or(0);
+        let alive = alive.unwrap_or(4);
+        let settings =
+            HookEscapeChanceSettings::from_counts(slippery_meat, &up_the_ante, salty_lips, alive)?;
+
+        Ok(Self {
+            slippery_meat,
+            up_the_ante,
+            salty_lips,
+            alive,
+            patch: patch.unwrap_or_default(),
+            settings,
+        })
+    }
+}
+
+/// Answer a chat message in one line, explaining what was wrong if it can't be answered.
+pub fn respond(message: &str) -> String {
+    match message.parse::<ChatQuery>() {
+        Ok(query) => query.reply(),
+        Err(e) => format!("Error: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::offering::Offering;
+    use crate::rule_set::RuleSet;
+
+    #[test]
+    fn parse_command() {
+        let query: ChatQuery = "!kobe sm3 uta3x2 lips2 alive3".parse().unwrap();
+        assert_eq!(query.slippery_meat, Some(Tier::III));
+        assert_eq!(query.up_the_ante, vec![Tier::III, Tier::III]);
+        assert_eq!(query.salty_lips, 2);
+        assert_eq!(query.alive, 3);
+        assert_eq!(query.patch, Patch::Live);
+        assert_eq!(
+            query.settings,
+            HookEscapeChanceSettings::from_counts(Some(Tier::III), &[Tier::III, Tier::III], 2, 3)
+                .unwrap()
+        );
+
+        let query: ChatQuery = "SMiii UTAii utaIx1 LIPS live".parse().unwrap();
+        assert_eq!(query.up_the_ante, vec![Tier::II, Tier::I]);
+        assert_eq!(query.salty_lips, 1);
+        assert_eq!(query.patch, Patch::Live);
+
+        let query: ChatQuery = "!kobe".parse().unwrap();
+        assert_eq!(query.settings, HookEscapeChanceSettings::new());
+        assert!((query.settings.calculate(&RuleSet::LIVE) - 0.115264).abs() < 0.00000000001);
+    }
+
+    #[test]
+    fn reply() {
+        assert_eq!(
+            respond("!kobe"),
+            "No perks or offerings, 4 alive: 11.53% to escape (4% per attempt, 3 attempts)"
+        );
+        assert_eq!(
+            respond("!kobe sm3 uta3x2 uta1 lips2 alive4 live"),
+            "Slippery Meat III + Up the Ante III x2 + Up the Ante I + 2 Salty Lips, 4 alive: 92.46% to escape (35% per attempt, 6 attempts)"
+        );
+        assert_eq!(
+            respond("!kobe uta3x3"),
+            "Up the Ante III x3, 4 alive: 67.15% to escape (31% per attempt, 3 attempts)"
+        );
+
+        // Counts can't make loadouts with warnings, but edited settings can
+        let mut query: ChatQuery = "!kobe".parse().unwrap();
+        query.settings.survivors[2].offering = Some(Offering::ChalkPouch);
+        assert_eq!(
+            query.reply(),
+            "No perks or offerings, 4 alive: 11.53% to escape (4% per attempt, 3 attempts). Warning: player 3 is not hooked, so their Chalk Pouch has no effect"
+        );
+        assert_eq!(
+            respond("!kobe uta3 alive2 original"),
+            "Up the Ante III, 2 alive (Original): 34.15% to escape (13% per attempt, 3 attempts)"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let parse = |s: &str| s.parse::<ChatQuery>().unwrap_err();
+        assert_eq!(parse("sm4"), ParseError::InvalidTier("sm4".to_string()));
+        assert_eq!(
+            parse("uta3xx"),
+            ParseError::InvalidCount("uta3xx".to_string())
+        );
+        assert_eq!(
+            parse("uta3x0"),
+            ParseError::InvalidCount("uta3x0".to_string())
+        );
+        assert_eq!(respond("!kobe uta3x0"), "Error: invalid count in `uta3x0`");
+        assert_eq!(
+            parse("uta3x"),
+            ParseError::InvalidCount("uta3x".to_string())
+        );
+        assert_eq!(
+            respond("!kobe sm4"),
+            "Error: invalid tier in `sm4`, expected 1-3 or I-III"
+        );
+        assert_eq!(
+            parse("alive"),
+            ParseError::InvalidCount("alive".to_string())
+        );
+        assert_eq!(parse("sm1 sm2"), ParseError::Duplicate("sm".to_string()));
+        assert_eq!(parse("dh"), ParseError::UnknownWord("dh".to_string()));
+        assert!(matches!(parse("uta3x5"), ParseError::Validation(_)));
+        assert!("uta3x4".parse::<ChatQuery>().is_ok());
+        assert_eq!(
+            respond("!kobe uta3x3 alive2"),
+            "Error: invalid settings: 3 copies of Up the Ante requested, but there are only 2 alive survivors"
+        );
+        assert!(respond("!kobe dh").starts_with("Error: unknown `dh`, try sm<tier>"));
+    }
+}
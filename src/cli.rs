@@ -0,0 +1,693 @@
+//! The headless command-line interface.
+//!
+//! Running the calculator with no subcommand opens the GUI.
+//! Release builds on Windows use the GUI subsystem,
+//! so when run with arguments they attach to the console they were started from to show their output (see `main.rs`).
+//! Output redirected to a file or pipe works either way.
+
+use anyhow::Context;
+use argh::FromArgs;
+use dbd_escape_chance_calculator::chance_format::Chance;
+use dbd_escape_chance_calculator::chance_format::ChanceFormat;
+use dbd_escape_chance_calculator::chance_format::ChanceStyle;
+use dbd_escape_chance_calculator::hook_escape_chance_settings::HookEscapeChanceSettings;
+use dbd_escape_chance_calculator::hook_escape_chance_settings::Tier;
+use dbd_escape_chance_calculator::hook_escape_chance_settings::TierList;
+use dbd_escape_chance_calculator::rule_set::Patch;
+use num::ToPrimitive;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::Read;
+use std::io::Write;
+#[cfg(feature = "server")]
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// An Escape Chance Calculator for Dead by Daylight. Opens the GUI if no subcommand is given.
+#[derive(Debug, FromArgs)]
+pub struct Options {
+    /// answer line-delimited JSON-RPC requests on stdin instead of opening the GUI
+    #[argh(switch)]
+    pub stdio: bool,
+
+    #[argh(subcommand)]
+    pub subcommand: Option<Subcommand>,
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand)]
+pub enum Subcommand {
+    Calculate(CalculateOptions),
+    Batch(BatchOptions),
+    Table(TableOptions),
+    Solve(SolveOptions),
+    #[cfg(feature = "server")]
+    Serve(ServeOptions),
+}
+
+/// Calculate the escape chance of the hooked survivor, as text or JSON, in a chosen style and # of decimals
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "calculate")]
+pub struct CalculateOptions {
+    /// the Slippery Meat tier of the hooked survivor, like III or 3
+    #[argh(option)]
+    pub slippery_meat: Option<Tier>,
+
+    /// comma-separated Up the Ante tiers of the alive survivors, like III,II
+    #[argh(option, default = "TierList::default()")]
+    pub up_the_ante: TierList,
+
+    /// the # of Vigo's Jars of Salty Lips burned
+    #[argh(option, default = "0")]
+    pub salty_lips: u8,
+
+    /// the # of alive survivors, including the hooked survivor
+    #[argh(option, default = "4")]
+    pub alive: u8,
+
+    /// the patch to use the rules of: live or original
+    #[argh(option, default = "Patch::Live")]
+    pub patch: Patch,
+
+    /// the output format: text or json
+    #[argh(option, default = "OutputFormat::Text")]
+    pub format: OutputFormat,
+
+    /// how to display the escape chance: percent, one-in, fraction or per-attempt
+    #[argh(option, default = "ChanceStyle::Percent")]
+    pub style: ChanceStyle,
+
+    /// the # of decimal places to display, up to 12
+    #[argh(option, default = "2", from_str_fn(parse_decimals))]
+    pub decimals: u8,
+}
+
+impl CalculateOptions {
+    /// Make settings from these options
+    pub fn settings(&self) -> anyhow::Result<HookEscapeChanceSettings> {
+        Ok(HookEscapeChanceSettings::from_counts(
+            self.slippery_meat,
+            &self.up_the_ante.0,
+            self.salty_lips,
+            self.alive,
+        )?)
+    }
+}
+
+/// Evaluate a CSV file of scenarios, writing the results as CSV and failing if any row has an error
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "batch")]
+pub struct BatchOptions {
+    /// the CSV file to read, or stdin if missing
+    #[argh(positional)]
+    pub input: Option<PathBuf>,
+
+    /// the CSV file to write, or stdout if missing
+    #[argh(option, short = 'o')]
+    pub output: Option<PathBuf>,
+
+    /// the patch to use the rules of: live or original
+    #[argh(option, default = "Patch::Live")]
+    pub patch: Patch,
+
+    /// how to display the escape chances: percent, one-in, fraction or per-attempt
+    #[argh(option, default = "ChanceStyle::Percent")]
+    pub style: ChanceStyle,
+
+    /// the # of decimal places to display, up to 12
+    #[argh(option, default = "2", from_str_fn(parse_decimals))]
+    pub decimals: u8,
+}
+
+/// Generate the table of every legal scenario, with one row per equivalence class
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "table")]
+pub struct TableOptions {
+    /// the table format: csv or markdown
+    #[argh(option, default = "TableFormat::Csv")]
+    pub format: TableFormat,
+
+    /// how to display the escape chances: percent, one-in, fraction or per-attempt
+    #[argh(option, default = "ChanceStyle::Percent")]
+    pub style: ChanceStyle,
+
+    /// the # of decimal places to display, up to 12
+    #[argh(option, default = "2", from_str_fn(parse_decimals))]
+    pub decimals: u8,
+
+    /// the file to write, or stdout if missing
+    #[argh(option, short = 'o')]
+    pub output: Option<PathBuf>,
+
+    /// the patch to use the rules of: live or original
+    #[argh(option, default = "Patch::Live")]
+    pub patch: Patch,
+
+    /// write a row for every loadout, instead of one per equivalence class
+    #[argh(switch)]
+    pub all_loadouts: bool,
+}
+
+/// Find the cheapest loadouts that reach a target escape chance
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "solve")]
+pub struct SolveOptions {
+    /// the escape chance to reach, like 50% or 0.5
+    #[argh(positional, from_str_fn(parse_target))]
+    pub target: f64,
+
+    /// the patch to use the rules of: live or original
+    #[argh(option, default = "Patch::Live")]
+    pub patch: Patch,
+
+    /// the most loadouts to show, 10 by default
+    #[argh(option, default = "10")]
+    pub limit: usize,
+
+    /// the output format: text or json
+    #[argh(option, default = "OutputFormat::Text")]
+    pub format: OutputFormat,
+
+    /// how to display the escape chances: percent, one-in, fraction or per-attempt
+    #[argh(option, default = "ChanceStyle::Percent")]
+    pub style: ChanceStyle,
+
+    /// the # of decimal places to display, up to 12
+    #[argh(option, default = "2", from_str_fn(parse_decimals))]
+    pub decimals: u8,
+}
+
+impl SolveOptions {
+    /// Find the loadouts for these options, cheapest first
+    pub fn solutions(&self) -> Vec<SolveOutput> {
+        let rules = self.patch.rule_set();
+        let chance_format = ChanceFormat {
+            style: self.style,
+            decimals: self.decimals,
+        };
+
+        dbd_escape_chance_calculator::solver::solve(self.target, rules)
+            .into_iter()
+            .take(self.limit)
+            .map(|solution| SolveOutput {
+                loadout: describe_loadout(&solution.settings),
+                build_code: dbd_escape_chance_calculator::build_code::encode(&solution.settings),
+                escape_chance: solution.escape_chance,
+                formatted_escape_chance: chance_format
+                    .format(&Chance::of(&solution.settings, rules)),
+                num_slots: solution.cost.num_slots,
+                num_tier_levels: solution.cost.num_tier_levels,
+                settings: solution.settings,
+            })
+            .collect()
+    }
+}
+
+/// Serve the HTTP API
+#[cfg(feature = "server")]
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "serve")]
+pub struct ServeOptions {
+    /// the address to listen on, 127.0.0.1:3000 by default
+    #[argh(
+        option,
+        default = "dbd_escape_chance_calculator::server::DEFAULT_ADDRESS.parse().unwrap()"
+    )]
+    pub address: SocketAddr,
+}
+
+/// How tables are written
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TableFormat {
+    Csv,
+    Markdown,
+}
+
+impl FromStr for TableFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "markdown" | "md" => Ok(Self::Markdown),
+            _ => Err(format!("invalid format `{}`, expected csv or markdown", s)),
+        }
+    }
+}
+
+/// Parse a # of decimal places, rejecting more than [`ChanceFormat::MAX_DECIMALS`]
+fn parse_decimals(value: &str) -> Result<u8, String> {
+    let decimals: u8 = value
+        .parse()
+        .map_err(|e| format!("invalid decimals `{}`: {}", value, e))?;
+    if decimals > ChanceFormat::MAX_DECIMALS {
+        return Err(format!(
+            "invalid decimals `{}`, expected at most {}",
+            value,
+            ChanceFormat::MAX_DECIMALS
+        ));
+    }
+
+    Ok(decimals)
+}
+
+/// Parse a target escape chance, as a percentage like `50%` or a probability like `0.5`
+fn parse_target(value: &str) -> Result<f64, String> {
+    let trimmed = value.trim();
+    let target = match trimmed.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|percent| percent / 100.0),
+        None => trimmed.parse(),
+    }
+    .map_err(|e| format!("invalid target `{}`: {}", value, e))?;
+    if !(0.0..=1.0).contains(&target) {
+        return Err(format!(
+            "invalid target `{}`, expected between 0% and 100%",
+            value
+        ));
+    }
+
+    Ok(target)
+}
+
+/// How results are printed
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("invalid format `{}`, expected text or json", s)),
+        }
+    }
+}
+
+/// The result of a calculation, as printed in json
+#[derive(Debug, serde::Serialize)]
+pub struct CalculateOutput {
+    /// The patch that the rules were taken from
+    pub patch: String,
+
+    /// The chance of escaping on any attempt
+    pub escape_chance: f64,
+
+    /// [`Self::escape_chance`] as an exact fraction, like `1801/15625`
+    pub exact_escape_chance: String,
+
+    /// [`Self::escape_chance`] in the requested style
+    pub formatted_escape_chance: String,
+
+    /// The chance of escaping on a single attempt
+    pub escape_chance_per_attempt: f64,
+
+    /// The # of self-unhook attempts
+    pub num_tries: u8,
+
+    /// Warnings about parts of the loadout that have no effect
+    pub warnings: Vec<String>,
+}
+
+impl Display for CalculateOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Total Escape Chance: {}", self.formatted_escape_chance)
+    }
+}
+
+/// A loadout found by the solver, as printed in json
+#[derive(Debug, serde::Serialize)]
+pub struct SolveOutput {
+    /// The perks and offering of each survivor, like `player 1 (hooked): Slippery Meat III; ...`
+    pub loadout: String,
+
+    /// The build code of [`Self::settings`]
+    pub build_code: String,
+
+    /// The chance of escaping on any attempt
+    pub escape_chance: f64,
+
+    /// [`Self::escape_chance`] in the requested style
+    pub formatted_escape_chance: String,
+
+    /// The # of perk slots and offerings used
+    pub num_slots: u8,
+
+    /// The sum of all perk tiers, counting tier I as 1
+    pub num_tier_levels: u8,
+
+    /// The loadout, in the same format as saved settings
+    pub settings: HookEscapeChanceSettings,
+}
+
+impl Display for SolveOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} with {} slots ({}): {}",
+            self.formatted_escape_chance, self.num_slots, self.build_code, self.loadout
+        )
+    }
+}
+
+/// Describe the perks, offering and state of every survivor, like `player 1 (hooked): Slippery Meat III`
+fn describe_loadout(settings: &HookEscapeChanceSettings) -> String {
+    settings
+        .survivors
+        .iter()
+        .enumerate()
+        .map(|(index, survivor)| {
+            let mut parts: Vec<String> = survivor
+                .perks
+                .iter()
+                .flatten()
+                .map(|equipped| format!("{} {}", equipped.perk, equipped.tier))
+                .collect();
+            parts.extend(survivor.offering.map(|offering| offering.to_string()));
+            if parts.is_empty() {
+                parts.push("nothing".to_string());
+            }
+
+            let state = if index == usize::from(settings.hooked_survivor) {
+                " (hooked)"
+            } else if !survivor.alive {
+                " (dead)"
+            } else {
+                ""
+            };
+            format!("player {}{}: {}", index + 1, state, parts.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Run a subcommand.
+pub fn run(subcommand: Subcommand) -> anyhow::Result<()> {
+    match subcommand {
+        Subcommand::Calculate(options) => calculate(options),
+        Subcommand::Batch(options) => batch(options),
+        Subcommand::Table(options) => table(options),
+        Subcommand::Solve(options) => solve(options),
+        #[cfg(feature = "server")]
+        Subcommand::Serve(options) => serve(options),
+    }
+}
+
+/// Answer JSON-RPC requests on stdin until it closes.
+pub fn stdio() -> anyhow::Result<()> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    dbd_escape_chance_calculator::rpc::serve(stdin.lock(), stdout.lock())
+        .context("failed to answer requests")?;
+
+    Ok(())
+}
+
+/// Open a file for writing, or stdout if there is no path.
+fn create_output(path: Option<&PathBuf>) -> anyhow::Result<Box<dyn Write>> {
+    match path {
+        Some(path) => {
+            Ok(Box::new(File::create(path).with_context(|| {
+                format!("failed to create `{}`", path.display())
+            })?))
+        }
+        None => Ok(Box::new(std::io::stdout())),
+    }
+}
+
+fn batch(options: BatchOptions) -> anyhow::Result<()> {
+    let reader: Box<dyn Read> = match options.input.as_ref() {
+        Some(path) => Box::new(
+            File::open(path).with_context(|| format!("failed to open `{}`", path.display()))?,
+        ),
+        None => Box::new(std::io::stdin()),
+    };
+    let writer = create_output(options.output.as_ref())?;
+    let chance_format = ChanceFormat {
+        style: options.style,
+        decimals: options.decimals,
+    };
+
+    let errors = dbd_escape_chance_calculator::batch::evaluate(
+        reader,
+        writer,
+        options.patch.rule_set(),
+        &chance_format,
+    )
+    .context("failed to evaluate scenarios")?;
+    for error in errors.iter() {
+        eprintln!("error: {}", error);
+    }
+    if !errors.is_empty() {
+        anyhow::bail!("{} of the scenarios failed", errors.len());
+    }
+
+    Ok(())
+}
+
+fn table(options: TableOptions) -> anyhow::Result<()> {
+    let rules = options.patch.rule_set();
+    let rows = if options.all_loadouts {
+        dbd_escape_chance_calculator::table::generate_loadouts(rules)
+    } else {
+        dbd_escape_chance_calculator::table::generate(rules)
+    };
+    let writer = create_output(options.output.as_ref())?;
+    let chance_format = ChanceFormat {
+        style: options.style,
+        decimals: options.decimals,
+    };
+
+    match options.format {
+        TableFormat::Csv => {
+            dbd_escape_chance_calculator::table::write_csv(&rows, &chance_format, writer)?
+        }
+        TableFormat::Markdown => {
+            dbd_escape_chance_calculator::table::write_markdown(&rows, &chance_format, writer)?
+        }
+    }
+
+    Ok(())
+}
+
+fn solve(options: SolveOptions) -> anyhow::Result<()> {
+    let solutions = options.solutions();
+    if solutions.is_empty() {
+        anyhow::bail!("no loadout reaches the target");
+    }
+
+    match options.format {
+        OutputFormat::Text => {
+            for solution in solutions.iter() {
+                println!("{}", solution);
+            }
+        }
+        OutputFormat::Json => {
+            let json =
+                serde_json::to_string_pretty(&solutions).context("failed to serialize output")?;
+            println!("{}", json);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "server")]
+fn serve(options: ServeOptions) -> anyhow::Result<()> {
+    let runtime = tokio::runtime::Runtime::new().context("failed to start tokio runtime")?;
+    eprintln!("Listening on http://{}", options.address);
+    runtime.block_on(dbd_escape_chance_calculator::server::serve(options.address));
+
+    Ok(())
+}
+
+fn calculate(options: CalculateOptions) -> anyhow::Result<()> {
+    let settings = options.settings()?;
+    let rules = options.patch.rule_set();
+    settings.try_calculate(rules)?;
+    let chance = Chance::of(&settings, rules);
+    let chance_format = ChanceFormat {
+        style: options.style,
+        decimals: options.decimals,
+    };
+    let output = CalculateOutput {
+        patch: options.patch.to_string(),
+        escape_chance: chance.escape_chance.to_f64().unwrap_or(f64::NAN),
+        exact_escape_chance: chance.escape_chance.to_string(),
+        formatted_escape_chance: chance_format.format(&chance),
+        escape_chance_per_attempt: chance
+            .escape_chance_per_attempt
+            .to_f64()
+            .unwrap_or(f64::NAN),
+        num_tries: chance.num_tries,
+        warnings: settings
+            .validate()
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect(),
+    };
+
+    match options.format {
+        OutputFormat::Text => {
+            for warning in output.warnings.iter() {
+                eprintln!("warning: {}", warning);
+            }
+            println!("{}", output);
+        }
+        OutputFormat::Json => {
+            let json =
+                serde_json::to_string_pretty(&output).context("failed to serialize output")?;
+            println!("{}", json);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn calculate_options() {
+        let options = CalculateOptions::from_args(
+            &["calculate"],
+            &[
+                "--slippery-meat",
+                "III",
+                "--up-the-ante",
+                "III,II",
+                "--salty-lips",
+                "2",
+                "--alive",
+                "3",
+                "--format",
+                "json",
+            ],
+        )
+        .unwrap();
+        assert_eq!(options.slippery_meat, Some(Tier::III));
+        assert_eq!(options.up_the_ante, TierList(vec![Tier::III, Tier::II]));
+        assert_eq!(options.format, OutputFormat::Json);
+
+        let settings = options.settings().unwrap();
+        assert_eq!(settings.num_alive_survivors(), 3);
+
+        let options = CalculateOptions::from_args(&["calculate"], &[]).unwrap();
+        let chance = options
+            .settings()
+            .unwrap()
+            .calculate(options.patch.rule_set());
+        assert!((chance - 0.115264).abs() < 0.00000000001);
+    }
+
+    #[test]
+    fn bad_options() {
+        assert!(CalculateOptions::from_args(&["calculate"], &["--up-the-ante", "III,IV"]).is_err());
+        assert!(
+            CalculateOptions::from_args(&["calculate"], &["--alive", "5"])
+                .unwrap()
+                .settings()
+                .is_err()
+        );
+        assert!(CalculateOptions::from_args(
+            &["calculate"],
+            &["--up-the-ante", "III,III,III", "--alive", "2"]
+        )
+        .unwrap()
+        .settings()
+        .is_err());
+        assert!(CalculateOptions::from_args(
+            &["calculate"],
+            &["--up-the-ante", "III,III,III,III,III"]
+        )
+        .unwrap()
+        .settings()
+        .is_err());
+        assert!(CalculateOptions::from_args(
+            &["calculate"],
+            &["--up-the-ante", "III", "--alive", "2"]
+        )
+        .unwrap()
+        .settings()
+        .is_ok());
+
+        assert!(CalculateOptions::from_args(&["calculate"], &["--decimals", "200"]).is_err());
+        assert!(TableOptions::from_args(&["table"], &["--decimals", "13"]).is_err());
+        assert!(
+            TableOptions::from_args(&["table"], &["--all-loadouts"])
+                .unwrap()
+                .all_loadouts
+        );
+        assert_eq!(
+            BatchOptions::from_args(&["batch"], &["--decimals", "12"])
+                .unwrap()
+                .decimals,
+            12
+        );
+    }
+
+    #[test]
+    fn batch_fails_on_bad_rows() {
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("batch-input-{}.csv", std::process::id()));
+        let output = dir.join(format!("batch-output-{}.csv", std::process::id()));
+        let options = || {
+            BatchOptions::from_args(
+                &["batch"],
+                &[input.to_str().unwrap(), "-o", output.to_str().unwrap()],
+            )
+            .unwrap()
+        };
+
+        std::fs::write(&input, "alive\n4\n3\n").unwrap();
+        assert!(batch(options()).is_ok());
+
+        std::fs::write(&input, "alive\n4\n5\n").unwrap();
+        let error = batch(options()).unwrap_err();
+        assert_eq!(error.to_string(), "1 of the scenarios failed");
+        assert_eq!(std::fs::read_to_string(&output).unwrap().lines().count(), 3);
+
+        std::fs::remove_file(&input).unwrap();
+        std::fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    fn solve_options() {
+        let options = SolveOptions::from_args(&["solve"], &["50%", "--limit", "3"]).unwrap();
+        assert_eq!(options.target, 0.5);
+
+        let solutions = options.solutions();
+        assert_eq!(solutions.len(), 3);
+        assert!(solutions
+            .iter()
+            .all(|solution| solution.escape_chance >= 0.5));
+        assert_eq!(solutions[0].num_slots, 2);
+        assert!(solutions[0]
+            .loadout
+            .starts_with("player 1 (hooked): Slippery Meat"));
+        assert_eq!(
+            dbd_escape_chance_calculator::build_code::decode(&solutions[0].build_code).unwrap(),
+            solutions[0].settings
+        );
+
+        let options = SolveOptions::from_args(&["solve"], &["0.1"]).unwrap();
+        let solutions = options.solutions();
+        assert_eq!(
+            solutions[0].to_string(),
+            format!(
+                "11.53% with 0 slots ({}): player 1 (hooked): nothing; player 2: nothing; player 3: nothing; player 4: nothing",
+                solutions[0].build_code
+            )
+        );
+
+        assert!(SolveOptions::from_args(&["solve"], &["150%"]).is_err());
+        assert!(SolveOptions::from_args(&["solve"], &["-0.1"]).is_err());
+        assert!(SolveOptions::from_args(&["solve"], &["half"]).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+/// The distribution of outcomes over a hooked survivor's self-unhook attempts.
+///
+/// Attempts are numbered starting from 1.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EscapeDistribution {
+    /// The chance of escaping on any single attempt
+    pub escape_chance_per_attempt: f64,
+
+    /// The # of self-unhook attempts
+    pub num_tries: u8,
+}
+
+impl EscapeDistribution {
+    pub fn new(escape_chance_per_attempt: f64, num_tries: u8) -> Self {
+        Self {
+            escape_chance_per_attempt,
+            num_tries,
+        }
+    }
+
+    /// The probability of failing every attempt up to and including the given attempt.
+    fn fail_through(&self, attempt: u8) -> f64 {
+        (1.0 - self.escape_chance_per_attempt).powi(i32::from(attempt.min(self.num_tries)))
+    }
+
+    /// The probability of escaping on exactly the given attempt.
+    ///
+    /// This is 0 for attempt 0 and for attempts past the last one.
+    pub fn escape_on_attempt(&self, attempt: u8) -> f64 {
+        if attempt == 0 || attempt > self.num_tries {
+            return 0.0;
+        }
+
+        self.fail_through(attempt - 1) * self.escape_chance_per_attempt
+    }
+
+    /// The probability of having escaped by the end of the given attempt.
+    pub fn escape_by_attempt(&self, attempt: u8) -> f64 {
+        1.0 - self.fail_through(attempt)
+    }
+
+    /// The probability of failing every attempt.
+    pub fn failure_chance(&self) -> f64 {
+        self.fail_through(self.num_tries)
+    }
+
+    /// The probability of escaping on any attempt.
+    pub fn escape_chance(&self) -> f64 {
+        1.0 - self.failure_chance()
+    }
+
+    /// The probability of escaping on any attempt, with the binomial theorem.
+    ///
+    /// This is `calcHookEscapeChanceBinomialTheorem` from `reference/escape-chance.js`,
+    /// and agrees with [`Self::escape_chance`] to within float error.
+    pub fn binomial_escape_chance(&self) -> f64 {
+        let escape_chance = self.escape_chance_per_attempt;
+        let num_tries = self.num_tries;
+        let x = 0u8;
+
+        let ret: f64 = (0..=x)
+            .map(|i| {
+                let binomial = num::integer::binomial(num_tries, i);
+
+                f64::from(binomial)
+                    * escape_chance.powi(i32::from(i))
+                    * (1.0 - escape_chance).powi(i32::from(num_tries - i))
+            })
+            .sum();
+
+        // This is the probability of failure. Invert it to get the success rate.
+        1.0 - ret
+    }
+
+    /// The expected # of attempts used, counting every attempt if the survivor never escapes.
+    pub fn expected_attempts_used(&self) -> f64 {
+        let escaped: f64 = (1..=self.num_tries)
+            .map(|attempt| f64::from(attempt) * self.escape_on_attempt(attempt))
+            .sum();
+
+        escaped + f64::from(self.num_tries) * self.failure_chance()
+    }
+
+    /// Iterate over the probability of escaping on each attempt, in order.
+    pub fn iter_attempts(&self) -> impl Iterator<Item = f64> + '_ {
+        (1..=self.num_tries).map(move |attempt| self.escape_on_attempt(attempt))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+    use crate::hook_escape_chance_settings::Tier;
+    use crate::rule_set::RuleSet;
+    use crate::survivor::Perk;
+
+    #[test]
+    fn matches_calculate() {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, Some(Tier::III));
+        settings.survivors[1].set_perk_tier(Perk::UpTheAnte, Some(Tier::II));
+        let distribution = settings.distribution(&RuleSet::LIVE);
+
+        assert!(
+            (distribution.escape_chance() - settings.calculate(&RuleSet::LIVE)).abs()
+                < 0.00000000001
+        );
+        assert!(
+            (distribution.iter_attempts().sum::<f64>() + distribution.failure_chance() - 1.0).abs()
+                < 0.00000000001
+        );
+        assert!(
+            (distribution.escape_by_attempt(distribution.num_tries)
+                - settings.calculate(&RuleSet::LIVE))
+            .abs()
+                < 0.00000000001
+        );
+    }
+
+    #[test]
+    fn default_distribution() {
+        let distribution = HookEscapeChanceSettings::new().distribution(&RuleSet::LIVE);
+
+        assert_eq!(distribution.num_tries, 3);
+        assert!((distribution.escape_on_attempt(1) - 0.04).abs() < 0.00000000001);
+        assert!((distribution.escape_on_attempt(2) - 0.0384).abs() < 0.00000000001);
+        assert!((distribution.escape_on_attempt(3) - 0.036864).abs() < 0.00000000001);
+        assert_eq!(distribution.escape_on_attempt(4), 0.0);
+        assert!((distribution.escape_by_attempt(2) - 0.0784).abs() < 0.00000000001);
+        assert!((distribution.failure_chance() - 0.884736).abs() < 0.00000000001);
+        assert!((distribution.expected_attempts_used() - 2.8816).abs() < 0.00000000001);
+    }
+}
@@ -0,0 +1,186 @@
+//! Step-by-step derivations of the escape chance.
+
+use crate::chance_format::format_percent;
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::luck_modifier::Contribution;
+use crate::luck_modifier::LUCK_MODIFIERS;
+use crate::rule_set::to_rational;
+use crate::rule_set::RuleSet;
+use num::BigRational;
+use num::One;
+use std::fmt::Display;
+
+/// One step of the calculation
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// The escape chance of a single attempt with no luck
+    BaseEscapeChance(BigRational),
+
+    /// The # of attempts with no perks
+    BaseNumTries(u8),
+
+    /// A perk or offering that adds luck or attempts
+    Contribution(Contribution),
+
+    /// The escape chance of a single attempt, with all luck added
+    EscapeChancePerAttempt(BigRational),
+
+    /// The # of attempts, with all extra attempts added
+    NumTries(u8),
+
+    /// The chance of failing a single attempt, `1 - p`
+    FailureChancePerAttempt(BigRational),
+
+    /// The chance of failing every attempt, `(1 - p)^n`
+    FailureChance { chance: BigRational, num_tries: u8 },
+
+    /// The chance of escaping on any attempt, `1 - (1 - p)^n`
+    EscapeChance(BigRational),
+}
+
+/// Format a percent, dropping trailing zeros.
+fn percent(value: &BigRational) -> String {
+    let percent = format_percent(value, 4);
+    let number = percent
+        .trim_end_matches('%')
+        .trim_end_matches('0')
+        .trim_end_matches('.');
+
+    format!("{}%", number)
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BaseEscapeChance(chance) => {
+                write!(f, "Base escape chance per attempt: {}", percent(chance))
+            }
+            Self::BaseNumTries(num_tries) => write!(f, "Base attempts: {}", num_tries),
+            Self::Contribution(contribution) => {
+                write!(
+                    f,
+                    "{} (Player {}): +{} luck",
+                    contribution.source,
+                    contribution.survivor + 1,
+                    percent(&contribution.exact_luck())
+                )?;
+                if contribution.extra_tries != 0 {
+                    write!(f, ", +{} attempts", contribution.extra_tries)?;
+                }
+
+                Ok(())
+            }
+            Self::EscapeChancePerAttempt(chance) => {
+                write!(
+                    f,
+                    "Total escape chance per attempt (p): {}",
+                    percent(chance)
+                )
+            }
+            Self::NumTries(num_tries) => write!(f, "Total attempts (n): {}", num_tries),
+            Self::FailureChancePerAttempt(chance) => {
+                write!(f, "Failure chance per attempt (1 - p): {}", percent(chance))
+            }
+            Self::FailureChance { chance, num_tries } => write!(
+                f,
+                "Failure chance on all {} attempts ((1 - p)^n): {}",
+                num_tries,
+                percent(chance)
+            ),
+            Self::EscapeChance(chance) => {
+                write!(f, "Escape chance (1 - (1 - p)^n): {}", percent(chance))
+            }
+        }
+    }
+}
+
+/// The steps of a calculation, in order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub steps: Vec<Step>,
+}
+
+impl Explanation {
+    /// Render the steps as a Markdown numbered list.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("## Escape Chance\n\n");
+        for (index, step) in self.steps.iter().enumerate() {
+            markdown.push_str(&format!("{}. {}\n", index + 1, step));
+        }
+
+        markdown
+    }
+}
+
+/// Explain how the escape chance of the given settings is calculated.
+///
+/// This follows the same steps as [`HookEscapeChanceSettings::calculate_exact`].
+pub fn explain(settings: &HookEscapeChanceSettings, rules: &RuleSet) -> Explanation {
+    let mut steps = vec![
+        Step::BaseEscapeChance(to_rational(rules.base_escape_chance)),
+        Step::BaseNumTries(rules.base_num_tries),
+    ];
+    steps.extend(
+        LUCK_MODIFIERS
+            .iter()
+            .flat_map(|modifier| modifier.contributions(settings, rules))
+            .map(Step::Contribution),
+    );
+
+    let (escape_chance_per_attempt, num_tries) = settings.exact_distribution(rules);
+    let failure_chance_per_attempt = BigRational::one() - &escape_chance_per_attempt;
+    let failure_chance = num::pow(failure_chance_per_attempt.clone(), usize::from(num_tries));
+    let escape_chance = BigRational::one() - &failure_chance;
+
+    steps.push(Step::EscapeChancePerAttempt(escape_chance_per_attempt));
+    steps.push(Step::NumTries(num_tries));
+    steps.push(Step::FailureChancePerAttempt(failure_chance_per_attempt));
+    steps.push(Step::FailureChance {
+        chance: failure_chance,
+        num_tries,
+    });
+    steps.push(Step::EscapeChance(escape_chance));
+
+    Explanation { steps }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hook_escape_chance_settings::Tier;
+    use crate::offering::Offering;
+    use crate::survivor::Perk;
+
+    #[test]
+    fn explain_loadout() {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, Some(Tier::III));
+        settings.survivors[2].set_perk_tier(Perk::UpTheAnte, Some(Tier::II));
+        settings.survivors[3].offering = Some(Offering::VigosJarOfSaltyLips);
+
+        let explanation = explain(&settings, &RuleSet::LIVE);
+        assert_eq!(
+            explanation.steps.last(),
+            Some(&Step::EscapeChance(
+                settings.calculate_exact(&RuleSet::LIVE)
+            ))
+        );
+        assert_eq!(
+            explanation.to_markdown(),
+            "\
+## Escape Chance
+
+1. Base escape chance per attempt: 4%
+2. Base attempts: 3
+3. Slippery Meat III (Player 1): +4% luck, +3 attempts
+4. Up the Ante II x 3 other alive survivors (Player 3): +6% luck
+5. Vigo's Jar of Salty Lips (Player 4): +3% luck
+6. Total escape chance per attempt (p): 17%
+7. Total attempts (n): 6
+8. Failure chance per attempt (1 - p): 83%
+9. Failure chance on all 6 attempts ((1 - p)^n): 32.694%
+10. Escape chance (1 - (1 - p)^n): 67.306%
+"
+        );
+    }
+}
@@ -0,0 +1,431 @@
+//! A C ABI, for embedding the calculator in other tools.
+//!
+//! The header is `include/dbd_escape_chance_calculator.h`.
+//! cbindgen regenerates it into `OUT_DIR` when building with the `ffi` feature, and `tests/ffi.rs` checks that the two match.
+//! Settings are an opaque [`DbdSettings`], made with [`dbd_settings_new`] or [`dbd_settings_from_counts`]
+//! and freed with [`dbd_settings_free`].
+//! Functions that can fail return a [`DbdStatus`], and write their results through out pointers.
+//!
+//! Tiers are 0 for none, then 1 to 3.
+//! Survivors are indexes from 0 to 3.
+
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::hook_escape_chance_settings::Tier;
+use crate::offering::Offering;
+use crate::rule_set::Patch;
+use crate::survivor::Perk;
+use crate::survivor::Survivor;
+
+/// The live patch
+pub const DBD_PATCH_LIVE: u8 = 0;
+
+/// The original patch, with flat Up the Ante
+pub const DBD_PATCH_ORIGINAL: u8 = 1;
+
+/// Slippery Meat
+pub const DBD_PERK_SLIPPERY_MEAT: u8 = 0;
+
+/// Up the Ante
+pub const DBD_PERK_UP_THE_ANTE: u8 = 1;
+
+/// No offering
+pub const DBD_OFFERING_NONE: u8 = 0;
+
+/// Chalk Pouch
+pub const DBD_OFFERING_CHALK_POUCH: u8 = 1;
+
+/// Cream Chalk Pouch
+pub const DBD_OFFERING_CREAM_CHALK_POUCH: u8 = 2;
+
+/// Ivory Chalk Pouch
+pub const DBD_OFFERING_IVORY_CHALK_POUCH: u8 = 3;
+
+/// Salt Pouch
+pub const DBD_OFFERING_SALT_POUCH: u8 = 4;
+
+/// Black Salt Statuette
+pub const DBD_OFFERING_BLACK_SALT_STATUETTE: u8 = 5;
+
+/// Vigo's Jar of Salty Lips
+pub const DBD_OFFERING_VIGOS_JAR_OF_SALTY_LIPS: u8 = 6;
+
+/// The result of a function that can fail
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum DbdStatus {
+    /// The function succeeded
+    Ok = 0,
+
+    /// A required pointer was null
+    NullPointer = 1,
+
+    /// An argument was out of range, like a tier of 4
+    InvalidArgument = 2,
+
+    /// The settings don't describe a legal trial
+    InvalidSettings = 3,
+
+    /// Every perk slot of the survivor is full
+    PerkSlotsFull = 4,
+}
+
+/// Opaque settings for a trial
+#[derive(Debug, Clone)]
+pub struct DbdSettings(HookEscapeChanceSettings);
+
+fn parse_tier(tier: u8) -> Result<Option<Tier>, DbdStatus> {
+    match tier {
+        0 => Ok(None),
+        1 => Ok(Some(Tier::I)),
+        2 => Ok(Some(Tier::II)),
+        3 => Ok(Some(Tier::III)),
+        _ => Err(DbdStatus::InvalidArgument),
+    }
+}
+
+fn parse_patch(patch: u8) -> Result<Patch, DbdStatus> {
+    match patch {
+        DBD_PATCH_LIVE => Ok(Patch::Live),
+        DBD_PATCH_ORIGINAL => Ok(Patch::Original),
+        _ => Err(DbdStatus::InvalidArgument),
+    }
+}
+
+fn parse_perk(perk: u8) -> Result<Perk, DbdStatus> {
+    match perk {
+        DBD_PERK_SLIPPERY_MEAT => Ok(Perk::SlipperyMeat),
+        DBD_PERK_UP_THE_ANTE => Ok(Perk::UpTheAnte),
+        _ => Err(DbdStatus::InvalidArgument),
+    }
+}
+
+fn parse_offering(offering: u8) -> Result<Option<Offering>, DbdStatus> {
+    match offering {
+        DBD_OFFERING_NONE => Ok(None),
+        _ => Offering::ALL
+            .get(usize::from(offering) - 1)
+            .copied()
+            .map(Some)
+            .ok_or(DbdStatus::InvalidArgument),
+    }
+}
+
+/// Run a fallible operation on settings behind a pointer.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+unsafe fn with_settings<F>(settings: *mut DbdSettings, f: F) -> DbdStatus
+where
+    F: FnOnce(&mut HookEscapeChanceSettings) -> Result<(), DbdStatus>,
+{
+    match settings.as_mut() {
+        Some(settings) => f(&mut settings.0).err().unwrap_or(DbdStatus::Ok),
+        None => DbdStatus::NullPointer,
+    }
+}
+
+/// Run a fallible operation on a survivor behind a pointer.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+unsafe fn with_survivor<F>(settings: *mut DbdSettings, survivor: u8, f: F) -> DbdStatus
+where
+    F: FnOnce(&mut Survivor) -> Result<(), DbdStatus>,
+{
+    with_settings(settings, |settings| {
+        let survivor = settings
+            .survivors
+            .get_mut(usize::from(survivor))
+            .ok_or(DbdStatus::InvalidArgument)?;
+        f(survivor)
+    })
+}
+
+/// Make settings with 4 alive survivors and no perks or offerings, with survivor 0 on the hook.
+///
+/// Free them with [`dbd_settings_free`].
+#[no_mangle]
+pub extern "C" fn dbd_settings_new() -> *mut DbdSettings {
+    Box::into_raw(Box::new(DbdSettings(HookEscapeChanceSettings::new())))
+}
+
+/// Make settings from aggregate counts, writing them to `out`.
+///
+/// Survivor 0 is hooked and has Slippery Meat.
+/// Up the Ante is given to survivors 0, 1, 2 and 3 in order, so it can't outnumber the alive survivors.
+/// Salty Lips are burned starting from survivor 0.
+/// Survivors past the # of alive survivors are dead.
+/// Free the settings with [`dbd_settings_free`].
+///
+/// Returns [`DbdStatus::InvalidArgument`] for a tier out of range, or an Up the Ante tier of 0.
+/// Returns [`DbdStatus::InvalidSettings`] if `up_the_ante_len` is more than `num_alive_survivors`,
+/// or for more than 4 Salty Lips or a # of alive survivors outside 1 to 4.
+///
+/// # Safety
+/// `up_the_ante` must point to `up_the_ante_len` tiers, and may only be null if the length is 0.
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_settings_from_counts(
+    slippery_meat: u8,
+    up_the_ante: *const u8,
+    up_the_ante_len: usize,
+    num_salty_lips: u8,
+    num_alive_survivors: u8,
+    out: *mut *mut DbdSettings,
+) -> DbdStatus {
+    if out.is_null() || (up_the_ante.is_null() && up_the_ante_len != 0) {
+        return DbdStatus::NullPointer;
+    }
+    let up_the_ante = if up_the_ante_len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(up_the_ante, up_the_ante_len)
+    };
+
+    let result = (|| {
+        let slippery_meat = parse_tier(slippery_meat)?;
+        let up_the_ante = up_the_ante
+            .iter()
+            .map(|tier| parse_tier(*tier)?.ok_or(DbdStatus::InvalidArgument))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        HookEscapeChanceSettings::from_counts(
+            slippery_meat,
+            &up_the_ante,
+            num_salty_lips,
+            num_alive_survivors,
+        )
+        .map_err(|_| DbdStatus::InvalidSettings)
+    })();
+
+    match result {
+        Ok(settings) => {
+            *out = Box::into_raw(Box::new(DbdSettings(settings)));
+            DbdStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Copy settings, or return null if `settings` is null.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_settings_clone(settings: *const DbdSettings) -> *mut DbdSettings {
+    match settings.as_ref() {
+        Some(settings) => Box::into_raw(Box::new(settings.clone())),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Free settings. Passing null does nothing.
+///
+/// # Safety
+/// `settings` must be null or a pointer from this library that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_settings_free(settings: *mut DbdSettings) {
+    if !settings.is_null() {
+        drop(Box::from_raw(settings));
+    }
+}
+
+/// Set which survivor is on the hook.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_settings_set_hooked_survivor(
+    settings: *mut DbdSettings,
+    survivor: u8,
+) -> DbdStatus {
+    with_settings(settings, |settings| {
+        if usize::from(survivor) >= settings.survivors.len() {
+            return Err(DbdStatus::InvalidArgument);
+        }
+        settings.hooked_survivor = survivor;
+
+        Ok(())
+    })
+}
+
+/// Set whether a survivor is alive.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_settings_set_alive(
+    settings: *mut DbdSettings,
+    survivor: u8,
+    alive: bool,
+) -> DbdStatus {
+    with_survivor(settings, survivor, |survivor| {
+        survivor.alive = alive;
+        Ok(())
+    })
+}
+
+/// Set the tier of a survivor's perk, equipping or unequipping it as needed.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_settings_set_perk_tier(
+    settings: *mut DbdSettings,
+    survivor: u8,
+    perk: u8,
+    tier: u8,
+) -> DbdStatus {
+    with_survivor(settings, survivor, |survivor| {
+        if survivor.set_perk_tier(parse_perk(perk)?, parse_tier(tier)?) {
+            Ok(())
+        } else {
+            Err(DbdStatus::PerkSlotsFull)
+        }
+    })
+}
+
+/// Set the offering a survivor burned.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_settings_set_offering(
+    settings: *mut DbdSettings,
+    survivor: u8,
+    offering: u8,
+) -> DbdStatus {
+    with_survivor(settings, survivor, |survivor| {
+        survivor.offering = parse_offering(offering)?;
+        Ok(())
+    })
+}
+
+/// Increase the Slippery Meat tier of the hooked survivor.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_settings_increment_slippery_meat_tier(
+    settings: *mut DbdSettings,
+) -> DbdStatus {
+    with_settings(settings, |settings| {
+        settings.increment_slippery_meat_tier();
+        Ok(())
+    })
+}
+
+/// Decrease the Slippery Meat tier of the hooked survivor.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_settings_decrement_slippery_meat_tier(
+    settings: *mut DbdSettings,
+) -> DbdStatus {
+    with_settings(settings, |settings| {
+        settings.decrement_slippery_meat_tier();
+        Ok(())
+    })
+}
+
+/// Increase the Up the Ante tier of a survivor.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_settings_increment_up_the_ante_tier(
+    settings: *mut DbdSettings,
+    survivor: u8,
+) -> DbdStatus {
+    with_settings(settings, |settings| {
+        if usize::from(survivor) >= settings.survivors.len() {
+            return Err(DbdStatus::InvalidArgument);
+        }
+        settings.increment_up_the_ante_tier(survivor);
+
+        Ok(())
+    })
+}
+
+/// Decrease the Up the Ante tier of a survivor.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_settings_decrement_up_the_ante_tier(
+    settings: *mut DbdSettings,
+    survivor: u8,
+) -> DbdStatus {
+    with_settings(settings, |settings| {
+        if usize::from(survivor) >= settings.survivors.len() {
+            return Err(DbdStatus::InvalidArgument);
+        }
+        settings.decrement_up_the_ante_tier(survivor);
+
+        Ok(())
+    })
+}
+
+/// Calculate the escape chance with the rules of a patch, writing it to `out`.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_calculate(
+    settings: *const DbdSettings,
+    patch: u8,
+    out: *mut f64,
+) -> DbdStatus {
+    let (settings, out) = match (settings.as_ref(), out.as_mut()) {
+        (Some(settings), Some(out)) => (settings, out),
+        _ => return DbdStatus::NullPointer,
+    };
+    let rules = match parse_patch(patch) {
+        Ok(patch) => patch.rule_set(),
+        Err(status) => return status,
+    };
+
+    match settings.0.try_calculate(rules) {
+        Ok(escape_chance) => {
+            *out = escape_chance;
+            DbdStatus::Ok
+        }
+        Err(_) => DbdStatus::InvalidSettings,
+    }
+}
+
+/// Get the escape chance of a single attempt and the # of attempts with the rules of a patch.
+///
+/// # Safety
+/// `settings` must be null or a valid pointer from this library.
+/// The out pointers must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn dbd_distribution(
+    settings: *const DbdSettings,
+    patch: u8,
+    escape_chance_per_attempt: *mut f64,
+    num_tries: *mut u8,
+) -> DbdStatus {
+    let (settings, escape_chance_per_attempt, num_tries) = match (
+        settings.as_ref(),
+        escape_chance_per_attempt.as_mut(),
+        num_tries.as_mut(),
+    ) {
+        (Some(settings), Some(escape_chance_per_attempt), Some(num_tries)) => {
+            (settings, escape_chance_per_attempt, num_tries)
+        }
+        _ => return DbdStatus::NullPointer,
+    };
+    let rules = match parse_patch(patch) {
+        Ok(patch) => patch.rule_set(),
+        Err(status) => return status,
+    };
+
+    let distribution = settings.0.distribution(rules);
+    *escape_chance_per_attempt = distribution.escape_chance_per_attempt;
+    *num_tries = distribution.num_tries;
+
+    DbdStatus::Ok
+}
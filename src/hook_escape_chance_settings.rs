@@ -1,124 +1,274 @@
 //! Reference:  https://stattrek.com/online-calculator/binomial.aspx
 
-const BASE_ESCAPE_CHANCE: f64 = 0.04;
-const BASE_NUM_TRIES: u8 = 3;
+use crate::escape_distribution::EscapeDistribution;
+use crate::luck_modifier::LUCK_MODIFIERS;
+use crate::offering::Offering;
+use crate::rule_set::RuleSet;
+use crate::serialization::RawSettings;
+use crate::serialization::SettingsV1;
+use crate::survivor::Perk;
+use crate::survivor::Survivor;
+use crate::validation::ValidationError;
+use crate::validation::ValidationIssue;
+use num::BigRational;
+use num::One;
+use std::fmt::Display;
+use std::str::FromStr;
 
 // These are Roman Numerals, not acronyms
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum Tier {
     I,
     II,
     III,
 }
 
-#[derive(Debug)]
-pub struct HookEscapeChanceSettings {
-    /// Whether the user has slippery meat
-    pub slippery_meat: Option<Tier>,
+impl Tier {
+    /// Get the index of this tier, starting from 0 for tier I
+    pub fn index(self) -> usize {
+        match self {
+            Self::I => 0,
+            Self::II => 1,
+            Self::III => 2,
+        }
+    }
+}
 
-    /// Each player's up the ante perk
-    pub up_the_ante: [Option<Tier>; 4],
+impl Display for Tier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::I => "I".fmt(f),
+            Self::II => "II".fmt(f),
+            Self::III => "III".fmt(f),
+        }
+    }
+}
 
-    /// The # of salty lips
-    pub num_salty_lips: u8,
+/// An error for a string that is not a tier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTierError(pub String);
 
-    /// The # of alive survivors
-    pub num_alive_survivors: u8,
+impl Display for ParseTierError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid tier `{}`, expected I, II, III, 1, 2 or 3",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseTierError {}
+
+impl FromStr for Tier {
+    type Err = ParseTierError;
+
+    /// Parse a tier from Roman or Arabic numerals
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "I" | "1" => Ok(Self::I),
+            "II" | "2" => Ok(Self::II),
+            "III" | "3" => Ok(Self::III),
+            _ => Err(ParseTierError(s.to_string())),
+        }
+    }
+}
+
+/// A comma-separated list of tiers, like `III,II`
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct TierList(pub Vec<Tier>);
+
+impl FromStr for TierList {
+    type Err = ParseTierError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .filter(|tier| !tier.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(into = "SettingsV1", try_from = "RawSettings")]
+pub struct HookEscapeChanceSettings {
+    /// The survivors in the trial
+    pub survivors: [Survivor; 4],
+
+    /// The index of the survivor on the hook
+    pub hooked_survivor: u8,
 }
 
 impl HookEscapeChanceSettings {
     pub fn new() -> Self {
         Self {
-            slippery_meat: None,
-
-            up_the_ante: [None; 4],
+            survivors: [Survivor::new(); 4],
 
-            num_salty_lips: 0,
-
-            num_alive_survivors: 4,
+            hooked_survivor: 0,
         }
     }
 
-    pub fn calculate(&self) -> f64 {
-        let mut escape_chance = BASE_ESCAPE_CHANCE;
-        let mut num_tries = BASE_NUM_TRIES;
-
-        if let Some(tier) = self.slippery_meat {
-            escape_chance += match tier {
-                Tier::I => 0.02,
-                Tier::II => 0.03,
-                Tier::III => 0.04,
-            };
-            num_tries += 3;
+    /// Make settings from aggregate counts.
+    ///
+    /// Player 1 is hooked and has Slippery Meat.
+    /// Up the Ante is given to players 1, 2, 3 and 4 in order, so it can't outnumber the alive survivors.
+    /// Salty Lips are burned starting from player 1.
+    /// Players past the # of alive survivors are dead.
+    pub fn from_counts(
+        slippery_meat: Option<Tier>,
+        up_the_ante: &[Tier],
+        num_salty_lips: u8,
+        num_alive_survivors: u8,
+    ) -> Result<Self, ValidationError> {
+        let mut issues = Vec::new();
+        if up_the_ante.len() > 4 {
+            issues.push(ValidationIssue::TooManyPerks {
+                perk: Perk::UpTheAnte,
+                count: up_the_ante.len(),
+            });
+        }
+        if num_salty_lips > 4 {
+            issues.push(ValidationIssue::TooManyOfferings {
+                count: num_salty_lips,
+            });
+        }
+        if !(1..=4).contains(&num_alive_survivors) {
+            issues.push(ValidationIssue::InvalidNumAliveSurvivors {
+                count: num_alive_survivors,
+            });
+        } else if up_the_ante.len() <= 4 && up_the_ante.len() > usize::from(num_alive_survivors) {
+            issues.push(ValidationIssue::TooManyUpTheAnte {
+                count: up_the_ante.len(),
+                num_alive_survivors,
+            });
+        }
+        if let Some(error) = ValidationError::from_issues(issues) {
+            return Err(error);
         }
 
-        for tier in self.up_the_ante.iter().flatten() {
-            escape_chance += match tier {
-                Tier::I => 0.01 * f64::from(num::clamp(self.num_alive_survivors, 1, 4) - 1),
-                Tier::II => 0.02 * f64::from(num::clamp(self.num_alive_survivors, 1, 4) - 1),
-                Tier::III => 0.03 * f64::from(num::clamp(self.num_alive_survivors, 1, 4) - 1),
-            };
+        let mut settings = Self::new();
+        settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, slippery_meat);
+        for (index, tier) in up_the_ante.iter().enumerate() {
+            settings.survivors[index].set_perk_tier(Perk::UpTheAnte, Some(*tier));
+        }
+        for (index, survivor) in settings.survivors.iter_mut().enumerate() {
+            if index < usize::from(num_salty_lips) {
+                survivor.offering = Some(Offering::VigosJarOfSaltyLips);
+            }
+            survivor.alive = index < usize::from(num_alive_survivors);
         }
 
-        escape_chance += 0.03 * f64::from(num::clamp(self.num_salty_lips, 0, 4));
+        Ok(settings)
+    }
 
-        // Begin binomial calculation
-        {
-            let x = 0u8;
+    /// Get the survivor on the hook
+    pub fn hooked(&self) -> Option<&Survivor> {
+        self.survivors.get(usize::from(self.hooked_survivor))
+    }
 
-            let ret: f64 = (0..=x)
-                .map(|i| {
-                    let binomial = num::integer::binomial(num_tries, i);
+    /// Iterate over the survivors other than the one on the hook
+    pub fn teammates(&self) -> impl Iterator<Item = &Survivor> + '_ {
+        self.survivors
+            .iter()
+            .enumerate()
+            .filter(move |(index, _)| *index != usize::from(self.hooked_survivor))
+            .map(|(_, survivor)| survivor)
+    }
 
-                    f64::from(binomial)
-                        * escape_chance.powi(i32::from(i))
-                        * (1.0 - &escape_chance).powi(i32::from(num_tries - i))
-                })
-                .sum();
+    /// The # of alive survivors
+    pub fn num_alive_survivors(&self) -> u8 {
+        self.survivors
+            .iter()
+            .filter(|survivor| survivor.alive)
+            .count() as u8
+    }
+
+    /// Get the per-attempt escape chance and # of attempts, as a distribution over attempts.
+    pub fn distribution(&self, rules: &RuleSet) -> EscapeDistribution {
+        let (escape_chance, num_tries) = LUCK_MODIFIERS.iter().fold(
+            (rules.base_escape_chance, rules.base_num_tries),
+            |(escape_chance, num_tries), modifier| {
+                (
+                    escape_chance + modifier.luck(self, rules),
+                    num_tries + modifier.extra_tries(self, rules),
+                )
+            },
+        );
 
-            // This is the probability of failure. Invert it to get the success rate.
-            1.0 - ret
+        EscapeDistribution::new(escape_chance, num_tries)
+    }
+
+    /// Check these settings for problems.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        crate::validation::validate(self)
+    }
+
+    /// Calculate the escape chance, failing if the settings have any validation errors.
+    pub fn try_calculate(&self, rules: &RuleSet) -> Result<f64, ValidationError> {
+        match ValidationError::from_issues(self.validate()) {
+            Some(error) => Err(error),
+            None => Ok(self.calculate(rules)),
         }
     }
 
+    /// Calculate the escape chance.
+    ///
+    /// This does not check the settings for problems; see [`Self::try_calculate`].
+    pub fn calculate(&self, rules: &RuleSet) -> f64 {
+        self.distribution(rules).binomial_escape_chance()
+    }
+
+    /// Get the per-attempt escape chance as an exact fraction, and the # of attempts.
+    ///
+    /// Rule values are taken as the decimals they were written as; see [`crate::rule_set::to_rational`].
+    pub fn exact_distribution(&self, rules: &RuleSet) -> (BigRational, u8) {
+        LUCK_MODIFIERS.iter().fold(
+            (
+                crate::rule_set::to_rational(rules.base_escape_chance),
+                rules.base_num_tries,
+            ),
+            |(escape_chance, num_tries), modifier| {
+                (
+                    escape_chance + modifier.exact_luck(self, rules),
+                    num_tries + modifier.extra_tries(self, rules),
+                )
+            },
+        )
+    }
+
+    /// Calculate the escape chance as an exact fraction.
+    ///
+    /// This does not check the settings for problems.
+    pub fn calculate_exact(&self, rules: &RuleSet) -> BigRational {
+        let (escape_chance, num_tries) = self.exact_distribution(rules);
+
+        // The chance of failing every attempt, inverted
+        BigRational::one() - num::pow(BigRational::one() - escape_chance, usize::from(num_tries))
+    }
+
     pub fn increment_slippery_meat_tier(&mut self) {
-        self.slippery_meat = match self.slippery_meat {
-            None => Some(Tier::I),
-            Some(Tier::I) => Some(Tier::II),
-            Some(Tier::II) => Some(Tier::III),
-            Some(Tier::III) => Some(Tier::III),
-        };
+        if let Some(survivor) = self.survivors.get_mut(usize::from(self.hooked_survivor)) {
+            survivor.increment_perk_tier(Perk::SlipperyMeat);
+        }
     }
 
     pub fn decrement_slippery_meat_tier(&mut self) {
-        self.slippery_meat = match self.slippery_meat {
-            None => None,
-            Some(Tier::I) => None,
-            Some(Tier::II) => Some(Tier::I),
-            Some(Tier::III) => Some(Tier::II),
-        };
+        if let Some(survivor) = self.survivors.get_mut(usize::from(self.hooked_survivor)) {
+            survivor.decrement_perk_tier(Perk::SlipperyMeat);
+        }
     }
 
     pub fn increment_up_the_ante_tier(&mut self, index: u8) {
-        if let Some(up_the_ante) = self.up_the_ante.get_mut(usize::from(index)) {
-            *up_the_ante = match up_the_ante {
-                None => Some(Tier::I),
-                Some(Tier::I) => Some(Tier::II),
-                Some(Tier::II) => Some(Tier::III),
-                Some(Tier::III) => Some(Tier::III),
-            };
+        if let Some(survivor) = self.survivors.get_mut(usize::from(index)) {
+            survivor.increment_perk_tier(Perk::UpTheAnte);
         }
     }
 
     pub fn decrement_up_the_ante_tier(&mut self, index: u8) {
-        if let Some(up_the_ante) = self.up_the_ante.get_mut(usize::from(index)) {
-            *up_the_ante = match up_the_ante {
-                None => None,
-                Some(Tier::I) => None,
-                Some(Tier::II) => Some(Tier::I),
-                Some(Tier::III) => Some(Tier::II),
-            };
+        if let Some(survivor) = self.survivors.get_mut(usize::from(index)) {
+            survivor.decrement_perk_tier(Perk::UpTheAnte);
         }
     }
 }
@@ -132,35 +282,239 @@ impl Default for HookEscapeChanceSettings {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::offering::Offering;
 
     #[test]
     fn default_escape_chance() {
-        let chance = HookEscapeChanceSettings::new().calculate();
+        let chance = HookEscapeChanceSettings::new().calculate(&RuleSet::LIVE);
         assert!((chance - 0.115264).abs() < 0.000001)
     }
 
     #[test]
     fn tier_3_slippery_meat() {
         let mut settings = HookEscapeChanceSettings::new();
-        settings.slippery_meat = Some(Tier::III);
-        let chance = settings.calculate();
+        settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, Some(Tier::III));
+        let chance = settings.calculate(&RuleSet::LIVE);
         assert!((chance - 0.39364499865).abs() < 0.00000000001)
     }
 
     #[test]
     fn tier_3_slippery_meat_up_the_ante() {
         let mut settings = HookEscapeChanceSettings::new();
-        settings.slippery_meat = Some(Tier::III);
-        settings.up_the_ante[0] = Some(Tier::III);
-        let chance = settings.calculate();
+        settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, Some(Tier::III));
+        settings.survivors[0].set_perk_tier(Perk::UpTheAnte, Some(Tier::III));
+        let chance = settings.calculate(&RuleSet::LIVE);
         assert!((chance - 0.673059626631).abs() < 0.00000000001)
     }
 
+    #[test]
+    fn slippery_meat_only_helps_hooked_survivor() {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[1].set_perk_tier(Perk::SlipperyMeat, Some(Tier::III));
+        let chance = settings.calculate(&RuleSet::LIVE);
+        assert!((chance - 0.115264).abs() < 0.00000000001);
+
+        settings.hooked_survivor = 1;
+        let chance = settings.calculate(&RuleSet::LIVE);
+        assert!((chance - 0.39364499865).abs() < 0.00000000001)
+    }
+
+    #[test]
+    fn up_the_ante_ignores_dead_holders() {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[1].set_perk_tier(Perk::UpTheAnte, Some(Tier::III));
+        settings.survivors[1].alive = false;
+        let chance = settings.calculate(&RuleSet::LIVE);
+        assert!((chance - 0.115264).abs() < 0.00000000001);
+
+        settings.survivors[2].set_perk_tier(Perk::UpTheAnte, Some(Tier::III));
+        let chance = settings.calculate(&RuleSet::LIVE);
+        assert!((chance - 0.271).abs() < 0.00000000001)
+    }
+
     #[test]
     fn salty_lips() {
         let mut settings = HookEscapeChanceSettings::new();
-        settings.num_salty_lips = 4;
-        let chance = settings.calculate();
+        for survivor in settings.survivors.iter_mut() {
+            survivor.offering = Some(Offering::VigosJarOfSaltyLips);
+        }
+        let chance = settings.calculate(&RuleSet::LIVE);
         assert!((chance - 0.407296).abs() < 0.00000000001)
     }
+
+    #[test]
+    fn chalk_pouch_only_helps_burner() {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[1].offering = Some(Offering::IvoryChalkPouch);
+        let chance = settings.calculate(&RuleSet::LIVE);
+        assert!((chance - 0.115264).abs() < 0.00000000001);
+
+        settings.survivors[0].offering = Some(Offering::IvoryChalkPouch);
+        let chance = settings.calculate(&RuleSet::LIVE);
+        assert!((chance - 0.195643).abs() < 0.00000000001);
+    }
+
+    #[test]
+    fn parse_tier() {
+        assert_eq!("III".parse(), Ok(Tier::III));
+        assert_eq!("ii".parse(), Ok(Tier::II));
+        assert_eq!("1".parse(), Ok(Tier::I));
+        assert_eq!(
+            "IV".parse::<Tier>(),
+            Err(ParseTierError(String::from("IV")))
+        );
+        assert_eq!(Tier::II.to_string().parse(), Ok(Tier::II));
+
+        assert_eq!("III, 2".parse(), Ok(TierList(vec![Tier::III, Tier::II])));
+        assert_eq!("".parse(), Ok(TierList::default()));
+        assert!("III,IV".parse::<TierList>().is_err());
+    }
+
+    #[test]
+    fn exact() {
+        let chance = HookEscapeChanceSettings::new().calculate_exact(&RuleSet::LIVE);
+        // 0.115264
+        assert_eq!(chance, BigRational::new(14_408.into(), 125_000.into()));
+
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, Some(Tier::III));
+        settings.survivors[1].set_perk_tier(Perk::UpTheAnte, Some(Tier::III));
+        let chance = settings.calculate_exact(&RuleSet::LIVE);
+        // 1 - 0.83^6
+        let expected = BigRational::one() - num::pow(BigRational::new(83.into(), 100.into()), 6);
+        assert_eq!(chance, expected);
+    }
+
+    /// Property tests, which need a random number generator that wasm32 doesn't have
+    #[cfg(not(target_arch = "wasm32"))]
+    mod properties {
+        use super::*;
+        use crate::luck_modifier::UpTheAnte;
+        use crate::rule_set::Patch;
+        use num::ToPrimitive;
+        use proptest::prelude::*;
+
+        fn tier() -> impl Strategy<Value = Option<Tier>> {
+            prop::option::of(prop::sample::select(&[Tier::I, Tier::II, Tier::III][..]))
+        }
+
+        fn survivor() -> impl Strategy<Value = Survivor> {
+            (
+                any::<bool>(),
+                tier(),
+                tier(),
+                prop::option::of(prop::sample::select(Offering::ALL)),
+            )
+                .prop_map(|(alive, slippery_meat, up_the_ante, offering)| {
+                    let mut survivor = Survivor::new();
+                    survivor.alive = alive;
+                    survivor.set_perk_tier(Perk::SlipperyMeat, slippery_meat);
+                    survivor.set_perk_tier(Perk::UpTheAnte, up_the_ante);
+                    survivor.offering = offering;
+                    survivor
+                })
+        }
+
+        /// Random settings without validation errors
+        fn settings() -> impl Strategy<Value = HookEscapeChanceSettings> {
+            (prop::array::uniform4(survivor()), 0..4u8).prop_map(|(survivors, hooked_survivor)| {
+                let mut settings = HookEscapeChanceSettings {
+                    survivors,
+                    hooked_survivor,
+                };
+                settings.survivors[usize::from(hooked_survivor)].alive = true;
+                settings
+            })
+        }
+
+        fn rules() -> impl Strategy<Value = &'static RuleSet> {
+            prop::sample::select(Patch::ALL).prop_map(Patch::rule_set)
+        }
+
+        proptest! {
+            #[test]
+            fn chance_is_probability(settings in settings(), rules in rules()) {
+                prop_assert!(settings.try_calculate(rules).is_ok());
+
+                let chance = settings.calculate(rules);
+                prop_assert!((0.0..=1.0).contains(&chance));
+            }
+
+            #[test]
+            fn exact_matches_calculate(settings in settings(), rules in rules()) {
+                let exact = settings.calculate_exact(rules);
+                let chance = settings.calculate(rules);
+                prop_assert!((exact.to_f64().unwrap() - chance).abs() < 0.00000000001);
+            }
+
+            #[test]
+            fn incrementing_never_hurts(settings in settings(), rules in rules(), index in 0..4usize) {
+                let chance = settings.calculate(rules);
+
+                for perk in [Perk::SlipperyMeat, Perk::UpTheAnte].iter().copied() {
+                    let mut incremented = settings.clone();
+                    incremented.survivors[index].increment_perk_tier(perk);
+                    prop_assert!(incremented.calculate(rules) >= chance);
+                }
+
+                if settings.survivors[index].offering.is_none() {
+                    let mut salted = settings.clone();
+                    salted.survivors[index].offering = Some(Offering::VigosJarOfSaltyLips);
+                    prop_assert!(salted.calculate(rules) >= chance);
+                }
+            }
+
+            #[test]
+            fn dying_never_helps(settings in settings(), rules in rules(), index in 0..4u8) {
+                prop_assume!(index != settings.hooked_survivor);
+                prop_assume!(settings.survivors[usize::from(index)].alive);
+
+                let chance = settings.calculate(rules);
+                let mut killed = settings.clone();
+                killed.survivors[usize::from(index)].alive = false;
+                let killed_chance = killed.calculate(rules);
+                prop_assert!(killed_chance <= chance);
+
+                // Any Up the Ante that was helping must lose some luck, if it scales with alive survivors
+                let has_up_the_ante = rules.per_other_alive_survivor(UpTheAnte::KEY) && settings
+                    .survivors
+                    .iter()
+                    .any(|survivor| survivor.alive && survivor.perk_tier(Perk::UpTheAnte).is_some());
+                if has_up_the_ante {
+                    prop_assert!(killed_chance < chance);
+                }
+            }
+
+            #[test]
+            fn increment_decrement_round_trip(settings in settings(), index in 0..4u8) {
+                let hooked = settings.hooked().unwrap();
+                if hooked.perk_tier(Perk::SlipperyMeat) != Some(Tier::III) {
+                    let mut round_trip = settings.clone();
+                    round_trip.increment_slippery_meat_tier();
+                    round_trip.decrement_slippery_meat_tier();
+                    prop_assert_eq!(&round_trip, &settings);
+                }
+                if hooked.perk_tier(Perk::SlipperyMeat).is_some() {
+                    let mut round_trip = settings.clone();
+                    round_trip.decrement_slippery_meat_tier();
+                    round_trip.increment_slippery_meat_tier();
+                    prop_assert_eq!(&round_trip, &settings);
+                }
+
+                let survivor = &settings.survivors[usize::from(index)];
+                if survivor.perk_tier(Perk::UpTheAnte) != Some(Tier::III) {
+                    let mut round_trip = settings.clone();
+                    round_trip.increment_up_the_ante_tier(index);
+                    round_trip.decrement_up_the_ante_tier(index);
+                    prop_assert_eq!(&round_trip, &settings);
+                }
+                if survivor.perk_tier(Perk::UpTheAnte).is_some() {
+                    let mut round_trip = settings.clone();
+                    round_trip.decrement_up_the_ante_tier(index);
+                    round_trip.increment_up_the_ante_tier(index);
+                    prop_assert_eq!(&round_trip, &settings);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,41 @@
+//! An Escape Chance Calculator for Dead by Daylight.
+//!
+//! Start with [`hook_escape_chance_settings::HookEscapeChanceSettings`], which describes a trial and calculates the escape chance.
+//! The GUI is in the binary, behind the default `gui` feature,
+//! so depend on this crate with `default-features = false` to leave out iced.
+//! Modules with their own dependencies are behind features of the same name, listed in `Cargo.toml`.
+
+#[cfg(feature = "batch")]
+pub mod batch;
+#[cfg(feature = "build-code")]
+pub mod build_code;
+pub mod chance_format;
+pub mod chat_command;
+pub mod escape_distribution;
+pub mod explanation;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod hook_escape_chance_settings;
+pub mod luck_modifier;
+pub mod offering;
+#[cfg(feature = "preferences")]
+pub mod preferences;
+#[cfg(test)]
+mod reference;
+pub mod report;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+pub mod rule_set;
+pub mod sensitivity;
+pub mod serialization;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+pub mod solver;
+pub mod survivor;
+#[cfg(feature = "table")]
+pub mod table;
+pub mod validation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,322 @@
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::hook_escape_chance_settings::Tier;
+use crate::offering::Offering;
+use crate::offering::OfferingTarget;
+use crate::rule_set::to_rational;
+use crate::rule_set::RuleSet;
+use crate::survivor::Perk;
+use num::BigRational;
+use std::fmt::Display;
+
+/// All known luck modifiers, in the order they are applied.
+pub const LUCK_MODIFIERS: &[&dyn LuckModifier] = &[&SlipperyMeat, &UpTheAnte, &LuckOfferings];
+
+/// What a contribution came from
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Source {
+    /// Slippery Meat on the hooked survivor
+    SlipperyMeat(Tier),
+
+    /// Up the Ante, which may scale with the # of other alive survivors
+    UpTheAnte {
+        tier: Tier,
+        /// The # of other alive survivors, if the rule set scales Up the Ante with it
+        num_other_alive_survivors: Option<u8>,
+    },
+
+    /// A luck offering
+    Offering(Offering),
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SlipperyMeat(tier) => write!(f, "{} {}", Perk::SlipperyMeat, tier),
+            Self::UpTheAnte {
+                tier,
+                num_other_alive_survivors: None,
+            } => write!(f, "{} {}", Perk::UpTheAnte, tier),
+            Self::UpTheAnte {
+                tier,
+                num_other_alive_survivors: Some(num_other_alive_survivors),
+            } => write!(
+                f,
+                "{} {} x {} other alive survivors",
+                Perk::UpTheAnte,
+                tier,
+                num_other_alive_survivors
+            ),
+            Self::Offering(offering) => offering.fmt(f),
+        }
+    }
+}
+
+/// The luck and extra attempts from one perk or offering
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contribution {
+    /// The index of the survivor with the perk or offering
+    pub survivor: u8,
+
+    /// The perk or offering
+    pub source: Source,
+
+    /// The luck the rule set grants the source, like `0.03`
+    pub rule_luck: f64,
+
+    /// How many times [`Self::rule_luck`] is added, like the # of other alive survivors for Up the Ante
+    pub multiplier: u8,
+
+    /// The # of extra self-unhook attempts
+    pub extra_tries: u8,
+}
+
+impl Contribution {
+    /// The luck added to each self-unhook attempt
+    pub fn luck(&self) -> f64 {
+        self.rule_luck * f64::from(self.multiplier)
+    }
+
+    /// [`Self::luck`], as an exact fraction of the rule set's decimal values
+    pub fn exact_luck(&self) -> BigRational {
+        to_rational(self.rule_luck) * BigRational::from_integer(self.multiplier.into())
+    }
+}
+
+/// A source of luck or extra self-unhook attempts.
+///
+/// Implementations only list their [`Contribution`]s, with values looked up in the [`RuleSet`] under their own key,
+/// so the totals can't disagree with the breakdown.
+pub trait LuckModifier {
+    /// The individual perks or offerings that make up this modifier, in survivor order
+    fn contributions(
+        &self,
+        settings: &HookEscapeChanceSettings,
+        rules: &RuleSet,
+    ) -> Vec<Contribution>;
+
+    /// The amount of luck this modifier adds to each self-unhook attempt
+    fn luck(&self, settings: &HookEscapeChanceSettings, rules: &RuleSet) -> f64 {
+        self.contributions(settings, rules)
+            .iter()
+            .map(Contribution::luck)
+            .sum()
+    }
+
+    /// [`Self::luck`], as an exact fraction of the rule set's decimal values
+    fn exact_luck(&self, settings: &HookEscapeChanceSettings, rules: &RuleSet) -> BigRational {
+        self.contributions(settings, rules)
+            .iter()
+            .map(Contribution::exact_luck)
+            .sum()
+    }
+
+    /// The # of extra self-unhook attempts this modifier grants
+    fn extra_tries(&self, settings: &HookEscapeChanceSettings, rules: &RuleSet) -> u8 {
+        self.contributions(settings, rules)
+            .iter()
+            .map(|contribution| contribution.extra_tries)
+            .sum()
+    }
+}
+
+/// Slippery Meat, which only helps the survivor holding it.
+#[derive(Debug, Copy, Clone)]
+pub struct SlipperyMeat;
+
+impl SlipperyMeat {
+    /// The key of this modifier's values in a [`RuleSet`]
+    pub const KEY: &'static str = "slippery_meat";
+}
+
+impl LuckModifier for SlipperyMeat {
+    fn contributions(
+        &self,
+        settings: &HookEscapeChanceSettings,
+        rules: &RuleSet,
+    ) -> Vec<Contribution> {
+        settings
+            .hooked()
+            .and_then(|survivor| survivor.perk_tier(Perk::SlipperyMeat))
+            .map(|tier| Contribution {
+                survivor: settings.hooked_survivor,
+                source: Source::SlipperyMeat(tier),
+                rule_luck: rules.luck(Self::KEY, tier),
+                multiplier: 1,
+                extra_tries: rules.extra_tries(Self::KEY, tier),
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Up the Ante, which counts for every alive survivor holding it.
+#[derive(Debug, Copy, Clone)]
+pub struct UpTheAnte;
+
+impl UpTheAnte {
+    /// The key of this modifier's values in a [`RuleSet`]
+    pub const KEY: &'static str = "up_the_ante";
+
+    /// The holders and tiers of the Up the Ante copies that count
+    fn tiers(settings: &HookEscapeChanceSettings) -> impl Iterator<Item = (u8, Tier)> + '_ {
+        settings
+            .survivors
+            .iter()
+            .enumerate()
+            .filter(|(_, survivor)| survivor.alive)
+            .filter_map(|(index, survivor)| {
+                survivor
+                    .perk_tier(Perk::UpTheAnte)
+                    .map(|tier| (index as u8, tier))
+            })
+    }
+}
+
+impl LuckModifier for UpTheAnte {
+    fn contributions(
+        &self,
+        settings: &HookEscapeChanceSettings,
+        rules: &RuleSet,
+    ) -> Vec<Contribution> {
+        let num_other_alive_survivors = if rules.per_other_alive_survivor(Self::KEY) {
+            Some(settings.num_alive_survivors().saturating_sub(1))
+        } else {
+            None
+        };
+
+        Self::tiers(settings)
+            .map(|(survivor, tier)| Contribution {
+                survivor,
+                source: Source::UpTheAnte {
+                    tier,
+                    num_other_alive_survivors,
+                },
+                rule_luck: rules.luck(Self::KEY, tier),
+                multiplier: num_other_alive_survivors.unwrap_or(1),
+                extra_tries: rules.extra_tries(Self::KEY, tier),
+            })
+            .collect()
+    }
+}
+
+/// Luck offerings, burned by any survivor.
+///
+/// Offerings that only target the burner only count if the hooked survivor burned them.
+#[derive(Debug, Copy, Clone)]
+pub struct LuckOfferings;
+
+impl LuckOfferings {
+    /// The key of the Chalk Pouch values in a [`RuleSet`], by tier
+    pub const CHALK_POUCH_KEY: &'static str = "chalk_pouch";
+
+    /// The key of the Salt Pouch values in a [`RuleSet`], by tier
+    pub const SALT_KEY: &'static str = "salt";
+
+    /// The key and tier of an offering's values in a [`RuleSet`]
+    pub fn rule(offering: Offering) -> (&'static str, Tier) {
+        match offering {
+            Offering::ChalkPouch => (Self::CHALK_POUCH_KEY, Tier::I),
+            Offering::CreamChalkPouch => (Self::CHALK_POUCH_KEY, Tier::II),
+            Offering::IvoryChalkPouch => (Self::CHALK_POUCH_KEY, Tier::III),
+            Offering::SaltPouch => (Self::SALT_KEY, Tier::I),
+            Offering::BlackSaltStatuette => (Self::SALT_KEY, Tier::II),
+            Offering::VigosJarOfSaltyLips => (Self::SALT_KEY, Tier::III),
+        }
+    }
+
+    /// The burners and offerings that count
+    fn offerings(settings: &HookEscapeChanceSettings) -> impl Iterator<Item = (u8, Offering)> + '_ {
+        let hooked_survivor = usize::from(settings.hooked_survivor);
+
+        settings
+            .survivors
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, survivor)| {
+                let offering = survivor.offering?;
+                match offering.target() {
+                    OfferingTarget::Burner if index != hooked_survivor => None,
+                    OfferingTarget::Burner | OfferingTarget::Team => Some((index as u8, offering)),
+                }
+            })
+    }
+}
+
+impl LuckModifier for LuckOfferings {
+    fn contributions(
+        &self,
+        settings: &HookEscapeChanceSettings,
+        rules: &RuleSet,
+    ) -> Vec<Contribution> {
+        Self::offerings(settings)
+            .map(|(survivor, offering)| {
+                let (key, tier) = Self::rule(offering);
+                Contribution {
+                    survivor,
+                    source: Source::Offering(offering),
+                    rule_luck: rules.luck(key, tier),
+                    multiplier: 1,
+                    extra_tries: rules.extra_tries(key, tier),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn totals_match_contributions() {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, Some(Tier::II));
+        settings.survivors[0].offering = Some(Offering::CreamChalkPouch);
+        settings.survivors[1].set_perk_tier(Perk::UpTheAnte, Some(Tier::III));
+        settings.survivors[2].offering = Some(Offering::VigosJarOfSaltyLips);
+        settings.survivors[3].alive = false;
+
+        for modifier in LUCK_MODIFIERS.iter() {
+            let contributions = modifier.contributions(&settings, &RuleSet::LIVE);
+            assert!(!contributions.is_empty());
+
+            let luck: f64 = contributions.iter().map(Contribution::luck).sum();
+            assert_eq!(modifier.luck(&settings, &RuleSet::LIVE), luck);
+        }
+        assert_eq!(UpTheAnte.luck(&settings, &RuleSet::LIVE), 0.03 * 2.0);
+        assert_eq!(SlipperyMeat.extra_tries(&settings, &RuleSet::LIVE), 3);
+    }
+
+    #[test]
+    fn up_the_ante_by_patch() {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[1].set_perk_tier(Perk::UpTheAnte, Some(Tier::III));
+        settings.survivors[3].alive = false;
+
+        let live = UpTheAnte.contributions(&settings, &RuleSet::LIVE);
+        assert_eq!(
+            live[0].source.to_string(),
+            "Up the Ante III x 2 other alive survivors"
+        );
+        assert!((UpTheAnte.luck(&settings, &RuleSet::LIVE) - 0.06).abs() < 0.00000000001);
+
+        let original = UpTheAnte.contributions(&settings, &RuleSet::ORIGINAL);
+        assert_eq!(original[0].source.to_string(), "Up the Ante III");
+        assert!((UpTheAnte.luck(&settings, &RuleSet::ORIGINAL) - 0.09).abs() < 0.00000000001);
+    }
+
+    #[test]
+    fn modifiers_without_rules_do_nothing() {
+        let rules = RuleSet {
+            modifiers: &[],
+            ..RuleSet::LIVE
+        };
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, Some(Tier::III));
+        settings.survivors[1].set_perk_tier(Perk::UpTheAnte, Some(Tier::III));
+
+        let distribution = settings.distribution(&rules);
+        assert_eq!(distribution.escape_chance_per_attempt, 0.04);
+        assert_eq!(distribution.num_tries, 3);
+    }
+}
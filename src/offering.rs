@@ -0,0 +1,65 @@
+use std::fmt::Display;
+
+/// A luck offering
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Offering {
+    ChalkPouch,
+    CreamChalkPouch,
+    IvoryChalkPouch,
+    SaltPouch,
+    BlackSaltStatuette,
+    VigosJarOfSaltyLips,
+}
+
+impl Offering {
+    /// All luck offerings
+    pub const ALL: &'static [Offering] = &[
+        Offering::ChalkPouch,
+        Offering::CreamChalkPouch,
+        Offering::IvoryChalkPouch,
+        Offering::SaltPouch,
+        Offering::BlackSaltStatuette,
+        Offering::VigosJarOfSaltyLips,
+    ];
+
+    /// The in-game name of this offering
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ChalkPouch => "Chalk Pouch",
+            Self::CreamChalkPouch => "Cream Chalk Pouch",
+            Self::IvoryChalkPouch => "Ivory Chalk Pouch",
+            Self::SaltPouch => "Salt Pouch",
+            Self::BlackSaltStatuette => "Black Salt Statuette",
+            Self::VigosJarOfSaltyLips => "Vigo's Jar of Salty Lips",
+        }
+    }
+
+    /// Who this offering grants luck to
+    pub fn target(self) -> OfferingTarget {
+        match self {
+            Self::ChalkPouch | Self::CreamChalkPouch | Self::IvoryChalkPouch => {
+                OfferingTarget::Burner
+            }
+            Self::SaltPouch | Self::BlackSaltStatuette | Self::VigosJarOfSaltyLips => {
+                OfferingTarget::Team
+            }
+        }
+    }
+}
+
+impl Display for Offering {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.name().fmt(f)
+    }
+}
+
+/// Who an offering grants luck to
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OfferingTarget {
+    /// Only the survivor who burned the offering
+    Burner,
+
+    /// Every survivor
+    Team,
+}
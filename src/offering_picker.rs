@@ -0,0 +1,94 @@
+use dbd_escape_chance_calculator::offering::Offering;
+use iced::PickList;
+use std::fmt::Display;
+
+/// The options shown in an offering picker, including no offering.
+const OFFERING_PICKER_ITEMS: &[OfferingPickerItem] = &[
+    OfferingPickerItem(None),
+    OfferingPickerItem(Some(Offering::ChalkPouch)),
+    OfferingPickerItem(Some(Offering::CreamChalkPouch)),
+    OfferingPickerItem(Some(Offering::IvoryChalkPouch)),
+    OfferingPickerItem(Some(Offering::SaltPouch)),
+    OfferingPickerItem(Some(Offering::BlackSaltStatuette)),
+    OfferingPickerItem(Some(Offering::VigosJarOfSaltyLips)),
+];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct OfferingPickerItem(Option<Offering>);
+
+impl Display for OfferingPickerItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(offering) => offering.fmt(f),
+            None => "none".fmt(f),
+        }
+    }
+}
+
+pub struct OfferingPickerState {
+    pick_list_state: iced::pick_list::State<OfferingPickerItem>,
+}
+
+impl OfferingPickerState {
+    pub fn new() -> Self {
+        Self {
+            pick_list_state: iced::pick_list::State::default(),
+        }
+    }
+}
+
+impl Default for OfferingPickerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct OfferingPicker<'a, F> {
+    state: &'a mut OfferingPickerState,
+    current: Option<Offering>,
+
+    on_change: F,
+}
+
+impl<'a, F> OfferingPicker<'a, F> {
+    pub fn new(
+        state: &'a mut OfferingPickerState,
+        current: Option<Offering>,
+        on_change: F,
+    ) -> Self {
+        Self {
+            state,
+            current,
+
+            on_change,
+        }
+    }
+}
+
+impl<'a, F, Message> OfferingPicker<'a, F>
+where
+    F: Fn(Option<Offering>) -> Message + 'static,
+    Message: Clone + 'static,
+{
+    pub fn into_element(self) -> iced::Element<'a, Message> {
+        let on_change = self.on_change;
+
+        PickList::new(
+            &mut self.state.pick_list_state,
+            OFFERING_PICKER_ITEMS,
+            Some(OfferingPickerItem(self.current)),
+            move |item| on_change(item.0),
+        )
+        .into()
+    }
+}
+
+impl<'a, F, Message> From<OfferingPicker<'a, F>> for iced::Element<'a, Message>
+where
+    F: Fn(Option<Offering>) -> Message + 'static,
+    Message: Clone + 'static,
+{
+    fn from(el: OfferingPicker<'a, F>) -> Self {
+        el.into_element()
+    }
+}
@@ -1,6 +1,6 @@
 use crate::assets::PerkImages;
 use crate::assets::PERK_ICON_SIZE;
-use crate::hook_escape_chance_settings::Tier;
+use dbd_escape_chance_calculator::hook_escape_chance_settings::Tier;
 use iced::Align;
 use iced::Button;
 use iced::Container;
@@ -0,0 +1,150 @@
+//! User preferences, saved between runs as TOML in the user's config directory.
+
+use crate::chance_format::ChanceFormat;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt::Display;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// An error that occured while saving or loading preferences
+#[derive(Debug)]
+pub enum Error {
+    /// The user has no config directory
+    NoConfigDir,
+
+    Io(std::io::Error),
+    TomlSerialize(toml::ser::Error),
+    TomlDeserialize(toml::de::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoConfigDir => "failed to locate the config directory".fmt(f),
+            Self::Io(e) => write!(f, "failed to access preferences: {}", e),
+            Self::TomlSerialize(e) => write!(f, "failed to serialize preferences: {}", e),
+            Self::TomlDeserialize(e) => write!(f, "invalid preferences: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NoConfigDir => None,
+            Self::Io(e) => Some(e),
+            Self::TomlSerialize(e) => Some(e),
+            Self::TomlDeserialize(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<toml::ser::Error> for Error {
+    fn from(e: toml::ser::Error) -> Self {
+        Self::TomlSerialize(e)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Self::TomlDeserialize(e)
+    }
+}
+
+/// User preferences
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// How the escape chance is displayed
+    pub chance_format: ChanceFormat,
+}
+
+impl Preferences {
+    /// The default preferences file path
+    pub fn path() -> Result<PathBuf, Error> {
+        let dir = dirs::config_dir().ok_or(Error::NoConfigDir)?;
+        Ok(dir
+            .join("dbd-escape-chance-calculator")
+            .join("preferences.toml"))
+    }
+
+    /// Load preferences from the default path, using the defaults if they were never saved.
+    pub fn load() -> Result<Self, Error> {
+        Self::load_from(&Self::path()?)
+    }
+
+    /// Save preferences to the default path.
+    pub fn save(&self) -> Result<(), Error> {
+        self.save_to(&Self::path()?)
+    }
+
+    /// Load preferences from a file, using the defaults if it does not exist.
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        match std::fs::read_to_string(path) {
+            Ok(data) => Ok(toml::from_str(&data)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save preferences to a file, creating its directory if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, toml::to_string(self)?)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chance_format::ChanceStyle;
+
+    #[test]
+    fn round_trip() {
+        let dir = std::env::temp_dir().join(format!(
+            "dbd-escape-chance-calculator-test-{}",
+            std::process::id()
+        ));
+        let path = dir.join("preferences.toml");
+
+        assert_eq!(
+            Preferences::load_from(&path).unwrap(),
+            Preferences::default()
+        );
+
+        let preferences = Preferences {
+            chance_format: ChanceFormat {
+                style: ChanceStyle::OneIn,
+                decimals: 3,
+            },
+        };
+        preferences.save_to(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[chance_format]\nstyle = \"one_in\"\ndecimals = 3\n"
+        );
+        assert_eq!(Preferences::load_from(&path).unwrap(), preferences);
+
+        std::fs::write(&path, "[chance_format]\nstyle = \"fraction\"\n").unwrap();
+        assert_eq!(
+            Preferences::load_from(&path).unwrap().chance_format,
+            ChanceFormat {
+                style: ChanceStyle::Fraction,
+                decimals: 2
+            }
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
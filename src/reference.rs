@@ -0,0 +1,256 @@
+//! Ports of the formulas in `reference/escape-chance.js`, and differential tests against them.
+//!
+//! The reference gives Up the Ante a flat 3/6/9% by tier, no matter how many survivors are alive.
+//! The live patch gives 1/2/3% per other alive survivor instead, which only agrees with the reference when all 4 survivors are alive.
+//! The ports are literal, so the differential tests against the live patch only compare inputs where both should agree,
+//! and the divergence has a test of its own. The original patch keeps the reference's flat values, and agrees everywhere.
+
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::hook_escape_chance_settings::Tier;
+use crate::rule_set::Patch;
+use crate::rule_set::RuleSet;
+
+/// `factorial`
+pub fn factorial(n: u8) -> f64 {
+    if n == 0 || n == 1 {
+        return 1.0;
+    }
+    f64::from(n) * factorial(n - 1)
+}
+
+/// `binomialCoefficient`
+pub fn binomial_coefficient(n: u8, k: u8) -> f64 {
+    factorial(n) / (factorial(k) * factorial(n - k))
+}
+
+/// `calcHookEscapeChanceBinomialTheorem`
+pub fn calc_hook_escape_chance_binomial_theorem(escape_chance: f64, num_tries: u8) -> f64 {
+    let x = 0;
+
+    let mut ret = 0.0;
+    for i in 0..=x {
+        ret += binomial_coefficient(num_tries, i)
+            * escape_chance.powi(i32::from(i))
+            * (1.0 - escape_chance).powi(i32::from(num_tries - i));
+    }
+    1.0 - ret
+}
+
+/// `calcHookEscapeChance`, a geometric series
+pub fn calc_hook_escape_chance(escape_chance: f64, num_tries: u8) -> f64 {
+    let mut ret = 0.0;
+    for i in 0..num_tries {
+        ret += (1.0 - escape_chance).powi(i32::from(i)) * escape_chance;
+    }
+
+    ret
+}
+
+/// The inputs of the reference script
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ReferenceInputs {
+    /// `numUpTheAnteI`, `numUpTheAnteII` and `numUpTheAnteIII`
+    pub num_up_the_ante: [u8; 3],
+
+    /// `numSaltyLips`
+    pub num_salty_lips: u8,
+
+    /// `hasSlipperyMeat`, which is tier III
+    pub has_slippery_meat: bool,
+}
+
+impl ReferenceInputs {
+    /// The per-attempt escape chance and # of attempts, as the reference script calculates them
+    pub fn luck(&self) -> (f64, u8) {
+        let mut num_tries = 3;
+        let mut chance = 0.04;
+        chance += 0.03 * f64::from(self.num_salty_lips);
+        chance += 0.03 * f64::from(self.num_up_the_ante[0]);
+        chance += 0.06 * f64::from(self.num_up_the_ante[1]);
+        chance += 0.09 * f64::from(self.num_up_the_ante[2]);
+
+        if self.has_slippery_meat {
+            num_tries += 3;
+            chance += 0.04;
+        }
+
+        (chance, num_tries)
+    }
+
+    /// The equivalent calculator settings
+    pub fn settings(&self, num_alive_survivors: u8) -> HookEscapeChanceSettings {
+        let up_the_ante: Vec<Tier> = [Tier::I, Tier::II, Tier::III]
+            .iter()
+            .zip(self.num_up_the_ante.iter())
+            .flat_map(|(tier, count)| vec![*tier; usize::from(*count)])
+            .collect();
+
+        HookEscapeChanceSettings::from_counts(
+            if self.has_slippery_meat {
+                Some(Tier::III)
+            } else {
+                None
+            },
+            &up_the_ante,
+            self.num_salty_lips,
+            num_alive_survivors,
+        )
+        .unwrap()
+    }
+}
+
+/// Every reference input that maps to settings without validation issues, with its # of alive survivors.
+fn all_inputs() -> Vec<(ReferenceInputs, u8)> {
+    let mut inputs = Vec::new();
+    for has_slippery_meat in [false, true].iter().copied() {
+        for num_salty_lips in 0..=4 {
+            for num_alive_survivors in 1..=4 {
+                let max_up_the_ante = num_alive_survivors;
+                for num_i in 0..=max_up_the_ante {
+                    for num_ii in 0..=max_up_the_ante - num_i {
+                        for num_iii in 0..=max_up_the_ante - num_i - num_ii {
+                            inputs.push((
+                                ReferenceInputs {
+                                    num_up_the_ante: [num_i, num_ii, num_iii],
+                                    num_salty_lips,
+                                    has_slippery_meat,
+                                },
+                                num_alive_survivors,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    inputs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TOLERANCE: f64 = 0.00000000001;
+
+    #[test]
+    fn reference_script() {
+        // The script's own inputs: 4 Salty Lips and nothing else
+        let inputs = ReferenceInputs {
+            num_salty_lips: 4,
+            ..ReferenceInputs::default()
+        };
+        let (chance, num_tries) = inputs.luck();
+        assert!((calc_hook_escape_chance(chance, num_tries) - 0.407296).abs() < TOLERANCE);
+        assert!(
+            (calc_hook_escape_chance_binomial_theorem(chance, num_tries) - 0.407296).abs()
+                < TOLERANCE
+        );
+    }
+
+    #[test]
+    fn formulas_agree() {
+        for num_tries in 0..=12 {
+            for i in 0..=100 {
+                let escape_chance = f64::from(i) / 100.0;
+                let geometric = calc_hook_escape_chance(escape_chance, num_tries);
+                let binomial = calc_hook_escape_chance_binomial_theorem(escape_chance, num_tries);
+                assert!((geometric - binomial).abs() < TOLERANCE);
+            }
+        }
+    }
+
+    /// Whether the calculator should agree with the reference: Up the Ante is only flat with all 4 survivors alive
+    fn should_agree(inputs: &ReferenceInputs, num_alive_survivors: u8) -> bool {
+        num_alive_survivors == 4 || inputs.num_up_the_ante == [0, 0, 0]
+    }
+
+    #[test]
+    fn calculate_matches_reference() {
+        let inputs = all_inputs();
+        assert_eq!(inputs.len(), 2 * 5 * (4 + 10 + 20 + 35));
+
+        let agreeing: Vec<_> = inputs
+            .into_iter()
+            .filter(|(inputs, num_alive_survivors)| should_agree(inputs, *num_alive_survivors))
+            .collect();
+        assert_eq!(agreeing.len(), 2 * 5 * (3 + 35));
+
+        for (inputs, num_alive_survivors) in agreeing {
+            let settings = inputs.settings(num_alive_survivors);
+            assert!(settings.validate().is_empty());
+
+            let (chance, num_tries) = inputs.luck();
+            let distribution = settings.distribution(&RuleSet::LIVE);
+            assert!((distribution.escape_chance_per_attempt - chance).abs() < TOLERANCE);
+            assert_eq!(distribution.num_tries, num_tries);
+
+            let calculated = settings.calculate(&RuleSet::LIVE);
+            let geometric = calc_hook_escape_chance(chance, num_tries);
+            let binomial = calc_hook_escape_chance_binomial_theorem(chance, num_tries);
+            assert!(
+                (calculated - geometric).abs() < TOLERANCE,
+                "{:?} with {} alive: {} != {}",
+                inputs,
+                num_alive_survivors,
+                calculated,
+                geometric
+            );
+            assert!((calculated - binomial).abs() < TOLERANCE);
+        }
+    }
+
+    #[test]
+    fn up_the_ante_scales_with_alive_survivors() {
+        // With survivors dead, the calculator gives Up the Ante less luck than the reference's flat amount
+        for (inputs, num_alive_survivors) in all_inputs() {
+            if should_agree(&inputs, num_alive_survivors) {
+                continue;
+            }
+
+            let (chance, num_tries) = inputs.luck();
+            let distribution = inputs
+                .settings(num_alive_survivors)
+                .distribution(&RuleSet::LIVE);
+            assert!(distribution.escape_chance_per_attempt < chance - TOLERANCE);
+            assert_eq!(distribution.num_tries, num_tries);
+        }
+
+        // 2 survivors alive: the calculator gives 3% where the reference gives 9%
+        let inputs = ReferenceInputs {
+            num_up_the_ante: [0, 0, 1],
+            ..ReferenceInputs::default()
+        };
+        let (chance, num_tries) = inputs.luck();
+        assert!((chance - (0.04 + 0.09)).abs() < TOLERANCE);
+        let calculated = inputs.settings(2).calculate(&RuleSet::LIVE);
+        assert!((calculated - calc_hook_escape_chance(0.04 + 0.03, num_tries)).abs() < TOLERANCE);
+        assert!((calculated - calc_hook_escape_chance(chance, num_tries)).abs() > 0.1);
+    }
+
+    #[test]
+    fn original_patch_matches_reference() {
+        let original = Patch::Original.rule_set();
+        let live = Patch::Live.rule_set();
+        for (inputs, num_alive_survivors) in all_inputs() {
+            let settings = inputs.settings(num_alive_survivors);
+            let (chance, num_tries) = inputs.luck();
+            let distribution = settings.distribution(original);
+            assert!((distribution.escape_chance_per_attempt - chance).abs() < TOLERANCE);
+            assert_eq!(distribution.num_tries, num_tries);
+
+            // The patches only differ for Up the Ante with fewer than 4 survivors alive
+            let difference = (settings.calculate(original) - settings.calculate(live)).abs();
+            if should_agree(&inputs, num_alive_survivors) {
+                assert!(difference < TOLERANCE);
+            } else {
+                assert!(
+                    difference > TOLERANCE,
+                    "{:?} with {} alive",
+                    inputs,
+                    num_alive_survivors
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! A serializable summary of a calculation, shared by the machine-readable interfaces.
+
+use crate::explanation::explain;
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::luck_modifier::LUCK_MODIFIERS;
+use crate::rule_set::Patch;
+use crate::validation::ValidationError;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The result of a calculation, with how it was reached
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    /// The patch that the rules were taken from
+    pub patch: Patch,
+
+    /// The chance of escaping on any attempt
+    pub escape_chance: f64,
+
+    /// [`Self::escape_chance`] as an exact fraction, like `1801/15625`
+    pub exact_escape_chance: String,
+
+    /// The chance of escaping on a single attempt
+    pub escape_chance_per_attempt: f64,
+
+    /// The # of self-unhook attempts
+    pub num_tries: u8,
+
+    /// The perks and offerings that add luck or attempts
+    pub breakdown: Vec<BreakdownEntry>,
+
+    /// The steps of the calculation, as in the GUI's explanation
+    pub steps: Vec<String>,
+
+    /// Warnings about parts of the loadout that have no effect
+    pub warnings: Vec<String>,
+}
+
+/// One perk or offering that adds luck or attempts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakdownEntry {
+    /// The index of the survivor with the perk or offering
+    pub survivor: u8,
+
+    /// The perk or offering, like `Slippery Meat III`
+    pub source: String,
+
+    /// The luck added to each self-unhook attempt
+    pub luck: f64,
+
+    /// The # of extra self-unhook attempts
+    pub extra_tries: u8,
+}
+
+impl Report {
+    /// Calculate the escape chance of settings with the rules of a patch.
+    ///
+    /// This fails if the settings don't describe a legal trial.
+    pub fn new(settings: &HookEscapeChanceSettings, patch: Patch) -> Result<Self, ValidationError> {
+        let rules = patch.rule_set();
+        let escape_chance = settings.try_calculate(rules)?;
+        let distribution = settings.distribution(rules);
+
+        Ok(Self {
+            patch,
+            escape_chance,
+            exact_escape_chance: settings.calculate_exact(rules).to_string(),
+            escape_chance_per_attempt: distribution.escape_chance_per_attempt,
+            num_tries: distribution.num_tries,
+            breakdown: LUCK_MODIFIERS
+                .iter()
+                .flat_map(|modifier| modifier.contributions(settings, rules))
+                .map(|contribution| BreakdownEntry {
+                    survivor: contribution.survivor,
+                    source: contribution.source.to_string(),
+                    luck: contribution.luck(),
+                    extra_tries: contribution.extra_tries,
+                })
+                .collect(),
+            steps: explain(settings, rules)
+                .steps
+                .iter()
+                .map(|step| step.to_string())
+                .collect(),
+            warnings: settings
+                .validate()
+                .iter()
+                .map(|issue| issue.to_string())
+                .collect(),
+        })
+    }
+}
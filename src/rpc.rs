@@ -0,0 +1,457 @@
+//! Line-delimited JSON-RPC 2.0, for integrations that spawn the calculator and talk to it over pipes.
+//!
+//! Each line of input is one request, and each response is written as one line.
+//! Notifications, which are valid requests with no `id`, get no response.
+//! Malformed requests, including lines that aren't valid UTF-8, are always answered, with a `null` `id`.
+//! Batches are not supported, and are answered with an invalid request error.
+//!
+//! # Methods
+//! Settings use the same JSON format as saved settings.
+//! `patch` is optional and defaults to `live`.
+//! * `calculate`: `{ "settings", "patch" }` to a [`Report`]
+//! * `validate`: `{ "settings" }` to a list of issues, each with a `severity` and a `message`
+//! * `explain`: `{ "settings", "patch" }` to `{ "steps", "markdown" }`
+//! * `encodeBuildCode`: `{ "settings" }` to a build code string, for settings that describe a legal trial
+//! * `decodeBuildCode`: `{ "code" }` to settings
+//!
+//! # Errors
+//! Besides the standard JSON-RPC error codes,
+//! [`INVALID_SETTINGS`] is returned for settings that can't be loaded or don't describe a legal trial,
+//! with the individual validation issues as its `data`,
+//! and [`INVALID_BUILD_CODE`] is returned for build codes that can't be decoded.
+
+use crate::build_code;
+use crate::explanation::explain;
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::report::Report;
+use crate::rule_set::Patch;
+use crate::validation::Severity;
+use crate::validation::ValidationError;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::BufRead;
+use std::io::Write;
+
+/// The input was not valid JSON
+pub const PARSE_ERROR: i64 = -32700;
+
+/// The input was not a JSON-RPC request
+pub const INVALID_REQUEST: i64 = -32600;
+
+/// The method does not exist
+pub const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The params were missing or of the wrong shape
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// The settings can't be loaded or don't describe a legal trial
+pub const INVALID_SETTINGS: i64 = -32000;
+
+/// The build code could not be decoded
+pub const INVALID_BUILD_CODE: i64 = -32001;
+
+/// A JSON-RPC error object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Error {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl Error {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
+/// Params with settings, which are loaded separately so bad settings get [`INVALID_SETTINGS`]
+#[derive(Debug, Deserialize)]
+struct SettingsParams {
+    settings: Value,
+    #[serde(default)]
+    patch: Patch,
+}
+
+impl SettingsParams {
+    /// Load the settings, or return an [`INVALID_SETTINGS`] error.
+    fn settings(&self) -> Result<HookEscapeChanceSettings, Error> {
+        HookEscapeChanceSettings::deserialize(&self.settings)
+            .map_err(|e| Error::new(INVALID_SETTINGS, format!("invalid settings: {}", e)))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CodeParams {
+    code: String,
+}
+
+/// An issue in the result of `validate`
+#[derive(Debug, Serialize)]
+struct Issue {
+    severity: Severity,
+    message: String,
+}
+
+/// The result of `explain`
+#[derive(Debug, Serialize)]
+struct ExplainResult {
+    steps: Vec<String>,
+    markdown: String,
+}
+
+/// Parse the params of a method.
+fn params<T: DeserializeOwned>(params: Value) -> Result<T, Error> {
+    serde_json::from_value(params).map_err(|e| Error::new(INVALID_PARAMS, e.to_string()))
+}
+
+/// Make an [`INVALID_SETTINGS`] error with the issues as data.
+fn invalid_settings(e: ValidationError) -> Error {
+    let issues: Vec<String> = e.issues.iter().map(|issue| issue.to_string()).collect();
+    Error {
+        code: INVALID_SETTINGS,
+        message: e.to_string(),
+        data: Some(issues.into()),
+    }
+}
+
+/// Run a method.
+fn call(method: &str, params_value: Value) -> Result<Value, Error> {
+    let result = match method {
+        "calculate" => {
+            let params: SettingsParams = params(params_value)?;
+            let report =
+                Report::new(&params.settings()?, params.patch).map_err(invalid_settings)?;
+            serde_json::to_value(report)
+        }
+        "validate" => {
+            let params: SettingsParams = params(params_value)?;
+            let issues: Vec<_> = params
+                .settings()?
+                .validate()
+                .iter()
+                .map(|issue| Issue {
+                    severity: issue.severity(),
+                    message: issue.to_string(),
+                })
+                .collect();
+            serde_json::to_value(issues)
+        }
+        "explain" => {
+            let params: SettingsParams = params(params_value)?;
+            let settings = params.settings()?;
+            if let Some(e) = ValidationError::from_issues(settings.validate()) {
+                return Err(invalid_settings(e));
+            }
+            let explanation = explain(&settings, params.patch.rule_set());
+            serde_json::to_value(ExplainResult {
+                steps: explanation
+                    .steps
+                    .iter()
+                    .map(|step| step.to_string())
+                    .collect(),
+                markdown: explanation.to_markdown(),
+            })
+        }
+        "encodeBuildCode" => {
+            let params: SettingsParams = params(params_value)?;
+            let settings = params.settings()?;
+            // Codes for illegal settings couldn't be decoded again
+            if let Some(e) = ValidationError::from_issues(settings.validate()) {
+                return Err(invalid_settings(e));
+            }
+            serde_json::to_value(build_code::encode(&settings))
+        }
+        "decodeBuildCode" => {
+            let params: CodeParams = params(params_value)?;
+            let settings = build_code::decode(&params.code)
+                .map_err(|e| Error::new(INVALID_BUILD_CODE, e.to_string()))?;
+            serde_json::to_value(settings)
+        }
+        _ => {
+            return Err(Error::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method `{}`", method),
+            ))
+        }
+    };
+
+    // Results are plain data, so this can only fail on a bug
+    Ok(result.expect("failed to serialize result"))
+}
+
+/// Check that a value is a JSON-RPC request, returning its id if it has one.
+fn parse_request(value: Value) -> Result<(Option<Value>, Request), Error> {
+    let id = value.get("id").cloned();
+    if let Some(id) = id.as_ref() {
+        if !(id.is_string() || id.is_number() || id.is_null()) {
+            return Err(Error::new(
+                INVALID_REQUEST,
+                "`id` must be a string, number or null",
+            ));
+        }
+    }
+
+    let request = serde_json::from_value::<Request>(value)
+        .map_err(|e| Error::new(INVALID_REQUEST, e.to_string()))?;
+    if request.jsonrpc != "2.0" {
+        return Err(Error::new(INVALID_REQUEST, "`jsonrpc` must be \"2.0\""));
+    }
+
+    Ok((id, request))
+}
+
+/// Handle one line of input, returning the response line if there is one.
+pub fn handle_line(line: &str) -> Option<String> {
+    let (id, result) = match serde_json::from_str::<Value>(line) {
+        Ok(value) => match parse_request(value) {
+            Ok((id, request)) => {
+                let result = call(&request.method, request.params);
+
+                // Notifications get no response, even if they fail
+                (id?, result)
+            }
+            // The id of a malformed request can't be trusted, so it is always null
+            Err(error) => (Value::Null, Err(error)),
+        },
+        Err(e) => (Value::Null, Err(Error::new(PARSE_ERROR, e.to_string()))),
+    };
+
+    Some(response_line(id, result))
+}
+
+/// Serialize the response to a request.
+fn response_line(id: Value, result: Result<Value, Error>) -> String {
+    let (result, error) = match result {
+        Ok(result) => (Some(result), None),
+        Err(error) => (None, Some(error)),
+    };
+    let response = Response {
+        jsonrpc: "2.0",
+        id,
+        result,
+        error,
+    };
+
+    serde_json::to_string(&response).expect("failed to serialize response")
+}
+
+/// Answer requests from a reader until it ends, writing responses to a writer.
+///
+/// Blank lines are skipped, and lines that aren't valid UTF-8 are answered with a parse error.
+/// This only fails if reading or writing fails.
+pub fn serve<R, W>(mut reader: R, mut writer: W) -> std::io::Result<()>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            break;
+        }
+
+        let response = match std::str::from_utf8(&buffer) {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => handle_line(line),
+            Err(e) => Some(response_line(
+                Value::Null,
+                Err(Error::new(PARSE_ERROR, format!("invalid UTF-8: {}", e))),
+            )),
+        };
+        if let Some(response) = response {
+            writeln!(writer, "{}", response)?;
+            writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hook_escape_chance_settings::Tier;
+
+    fn request(line: &str) -> Value {
+        serde_json::from_str(&handle_line(line).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn methods() {
+        let settings = HookEscapeChanceSettings::from_counts(None, &[], 4, 4).unwrap();
+        let response = request(
+            &serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "calculate",
+                "params": { "settings": settings },
+            })
+            .to_string(),
+        );
+        assert_eq!(response["id"], 1);
+        assert!(
+            (response["result"]["escape_chance"].as_f64().unwrap() - 0.407296).abs()
+                < 0.00000000001
+        );
+
+        let response = request(
+            r#"{"jsonrpc": "2.0", "id": "a", "method": "explain", "params": {"settings": {"version": 1, "hooked_survivor": 0, "survivors": [{"alive": true}, {"alive": true}, {"alive": true}, {"alive": true}]}, "patch": "live"}}"#,
+        );
+        assert_eq!(response["id"], "a");
+        assert!(response["result"]["markdown"]
+            .as_str()
+            .unwrap()
+            .starts_with("## Escape Chance"));
+
+        let settings = HookEscapeChanceSettings::from_counts(Some(Tier::III), &[], 0, 4).unwrap();
+        let response = request(
+            &serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "encodeBuildCode",
+                "params": { "settings": settings },
+            })
+            .to_string(),
+        );
+        let code = response["result"].as_str().unwrap();
+        let response = request(&format!(
+            r#"{{"jsonrpc": "2.0", "id": 3, "method": "decodeBuildCode", "params": {{"code": "{}"}}}}"#,
+            code
+        ));
+        let settings: HookEscapeChanceSettings =
+            serde_json::from_value(response["result"].clone()).unwrap();
+        assert_eq!(build_code::encode(&settings), code);
+
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[0].alive = false;
+        let line = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "validate",
+            "params": { "settings": settings },
+        })
+        .to_string();
+        let response = request(&line);
+        assert_eq!(response["result"][0]["severity"], "error");
+
+        for method in ["calculate", "explain", "encodeBuildCode"].iter() {
+            let response = request(&line.replace("validate", method));
+            assert_eq!(response["error"]["code"], INVALID_SETTINGS);
+            assert_eq!(response["error"]["data"].as_array().unwrap().len(), 1);
+        }
+
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.hooked_survivor = 4;
+        let response = request(
+            &serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 5,
+                "method": "encodeBuildCode",
+                "params": { "settings": settings },
+            })
+            .to_string(),
+        );
+        assert_eq!(response["error"]["code"], INVALID_SETTINGS);
+        assert!(response["result"].is_null());
+    }
+
+    #[test]
+    fn errors() {
+        let response = request("{");
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let response = request("[]");
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        let response = request(r#"{"jsonrpc": "1.0", "id": 1, "method": "calculate"}"#);
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        // Malformed requests are answered even without a usable id
+        let response = request(r#"{"jsonrpc": "2.0", "method": 1}"#);
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        let response = request(r#"{"jsonrpc": "2.0", "id": {}, "method": "calculate"}"#);
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        let response = request(r#"{"jsonrpc": "2.0", "id": 1, "method": "missing"}"#);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = request(r#"{"jsonrpc": "2.0", "id": 1, "method": "calculate"}"#);
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        for method in ["calculate", "validate", "explain", "encodeBuildCode"].iter() {
+            let response = request(&format!(
+                r#"{{"jsonrpc": "2.0", "id": 1, "method": "{}", "params": {{"settings": {{"version": 2}}}}}}"#,
+                method
+            ));
+            assert_eq!(response["error"]["code"], INVALID_SETTINGS, "{}", method);
+        }
+
+        let response = request(
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "decodeBuildCode", "params": {"code": "!"}}"#,
+        );
+        assert_eq!(response["error"]["code"], INVALID_BUILD_CODE);
+
+        assert!(handle_line(r#"{"jsonrpc": "2.0", "method": "missing"}"#).is_none());
+    }
+
+    #[test]
+    fn serve_lines() {
+        let input = "\n{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"calculate\", \"params\": {\"settings\": {\"version\": 1, \"hooked_survivor\": 0, \"survivors\": [{\"alive\": true}, {\"alive\": true}, {\"alive\": true}, {\"alive\": true}]}}}\n{\"jsonrpc\": \"2.0\", \"method\": \"calculate\"}\n{\n";
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("\"result\""));
+        assert!(lines[1].contains("-32700"));
+
+        // Lines that aren't UTF-8 are answered, and the lines after them still are
+        let mut input = b"{\"jsonrpc\": \"2.0\", \"id\": \"\xff\"}\n".to_vec();
+        input.extend_from_slice(br#"{"jsonrpc": "2.0", "id": 2, "method": "missing"}"#);
+        let mut output = Vec::new();
+        serve(&input[..], &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let response: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        let response: Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(response["id"], 2);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+}
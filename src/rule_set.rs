@@ -0,0 +1,224 @@
+//! The numbers behind the escape calculation, by game patch.
+//!
+//! Every value is cited where it is defined.
+//! Only values with a source are included, so patches can only be added along with the source of their values.
+//!
+//! Sources:
+//! * `reference/escape-chance.js`: The script the calculator was ported from
+//! * The original calculator's `calculate`, from the first commit of this repository
+//! * The offering descriptions listed on https://deadbydaylight.fandom.com/wiki/Luck
+
+use crate::hook_escape_chance_settings::Tier;
+use crate::luck_modifier::LuckOfferings;
+use crate::luck_modifier::SlipperyMeat;
+use crate::luck_modifier::UpTheAnte;
+use num::BigInt;
+use num::BigRational;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The values of one luck modifier, by tier
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ModifierRules {
+    /// The modifier these values are for, like [`crate::luck_modifier::SlipperyMeat::KEY`]
+    pub key: &'static str,
+
+    /// The luck granted, by tier
+    pub luck: [f64; 3],
+
+    /// The # of extra self-unhook attempts granted, by tier
+    pub extra_tries: [u8; 3],
+
+    /// Whether the luck is granted once per other alive survivor, instead of once
+    pub per_other_alive_survivor: bool,
+}
+
+const SLIPPERY_MEAT_RULES: ModifierRules = ModifierRules {
+    key: SlipperyMeat::KEY,
+    // The original calculator, by tier. `reference/escape-chance.js` only has tier III, `chance += 0.04;`
+    luck: [0.02, 0.03, 0.04],
+    // `reference/escape-chance.js`: `numTries += 3;`, which the original calculator gives every tier
+    extra_tries: [3, 3, 3],
+    per_other_alive_survivor: false,
+};
+
+const CHALK_POUCH_RULES: ModifierRules = ModifierRules {
+    key: LuckOfferings::CHALK_POUCH_KEY,
+    // The Luck page: Chalk Pouch, Cream Chalk Pouch and Ivory Chalk Pouch
+    luck: [0.01, 0.02, 0.03],
+    extra_tries: [0, 0, 0],
+    per_other_alive_survivor: false,
+};
+
+const SALT_RULES: ModifierRules = ModifierRules {
+    key: LuckOfferings::SALT_KEY,
+    // The Luck page: Salt Pouch and Black Salt Statuette.
+    // `reference/escape-chance.js` has Vigo's Jar of Salty Lips: `chance += (0.03 * numSaltyLips);`
+    luck: [0.01, 0.02, 0.03],
+    extra_tries: [0, 0, 0],
+    per_other_alive_survivor: false,
+};
+
+/// The numbers that drive the escape calculation for one game patch.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RuleSet {
+    /// The display name of this rule set
+    pub name: &'static str,
+
+    /// The chance of escaping on a single attempt, with no luck modifiers
+    pub base_escape_chance: f64,
+
+    /// The # of self-unhook attempts, with no perks
+    pub base_num_tries: u8,
+
+    /// The values of each luck modifier.
+    ///
+    /// Modifiers without values have no effect with this rule set.
+    pub modifiers: &'static [ModifierRules],
+}
+
+impl RuleSet {
+    /// The values of the live game, as of the sources above
+    pub const LIVE: RuleSet = RuleSet {
+        name: "Live",
+        // `reference/escape-chance.js`: `let chance = 0.04;`
+        base_escape_chance: 0.04,
+        // `reference/escape-chance.js`: `let numTries = 3;`
+        base_num_tries: 3,
+        modifiers: &[
+            SLIPPERY_MEAT_RULES,
+            ModifierRules {
+                key: UpTheAnte::KEY,
+                // The original calculator, per other alive survivor.
+                // `reference/escape-chance.js` has the values with all 4 survivors alive: 3/6/9%
+                luck: [0.01, 0.02, 0.03],
+                extra_tries: [0, 0, 0],
+                per_other_alive_survivor: true,
+            },
+            CHALK_POUCH_RULES,
+            SALT_RULES,
+        ],
+    };
+
+    /// The values of `reference/escape-chance.js`, where Up the Ante is flat instead of scaling with alive survivors
+    pub const ORIGINAL: RuleSet = RuleSet {
+        name: "Original",
+        // `reference/escape-chance.js`: `let chance = 0.04;`
+        base_escape_chance: 0.04,
+        // `reference/escape-chance.js`: `let numTries = 3;`
+        base_num_tries: 3,
+        modifiers: &[
+            SLIPPERY_MEAT_RULES,
+            ModifierRules {
+                key: UpTheAnte::KEY,
+                // `reference/escape-chance.js`: `chance += (0.03 * numUpTheAnteI);`, and 0.06 and 0.09 for tiers II and III
+                luck: [0.03, 0.06, 0.09],
+                extra_tries: [0, 0, 0],
+                per_other_alive_survivor: false,
+            },
+            CHALK_POUCH_RULES,
+            SALT_RULES,
+        ],
+    };
+
+    /// Get the values of a modifier, if it has any with this rule set
+    pub fn modifier(&self, key: &str) -> Option<&ModifierRules> {
+        self.modifiers.iter().find(|rules| rules.key == key)
+    }
+
+    /// The luck granted by a modifier at the given tier, or 0 if it has no values
+    pub fn luck(&self, key: &str, tier: Tier) -> f64 {
+        self.modifier(key)
+            .map_or(0.0, |rules| rules.luck[tier.index()])
+    }
+
+    /// Whether a modifier's luck is granted once per other alive survivor
+    pub fn per_other_alive_survivor(&self, key: &str) -> bool {
+        self.modifier(key)
+            .is_some_and(|rules| rules.per_other_alive_survivor)
+    }
+
+    /// The # of extra attempts granted by a modifier at the given tier, or 0 if it has no values
+    pub fn extra_tries(&self, key: &str, tier: Tier) -> u8 {
+        self.modifier(key)
+            .map_or(0, |rules| rules.extra_tries[tier.index()])
+    }
+}
+
+/// Convert a rule value to the exact decimal it was written as.
+///
+/// Rule values like `0.04` can't be stored exactly in an f64,
+/// so this uses the shortest decimal that converts back to the same f64.
+pub fn to_rational(value: f64) -> BigRational {
+    let value = value.to_string();
+    let (integer, fraction) = value.split_once('.').unwrap_or((&value, ""));
+    let numer: BigInt = format!("{}{}", integer, fraction)
+        .parse()
+        .expect("rule values must be finite");
+    let denom = num::pow(BigInt::from(10), fraction.len());
+
+    BigRational::new(numer, denom)
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::LIVE
+    }
+}
+
+/// A game patch with a known rule set
+#[derive(
+    Debug, Default, Copy, Clone, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Patch {
+    /// [`RuleSet::LIVE`]
+    #[default]
+    Live,
+
+    /// [`RuleSet::ORIGINAL`]
+    Original,
+}
+
+impl Patch {
+    /// All patches, newest first
+    pub const ALL: &'static [Patch] = &[Patch::Live, Patch::Original];
+
+    /// Get the rule set for this patch
+    pub fn rule_set(self) -> &'static RuleSet {
+        match self {
+            Self::Live => &RuleSet::LIVE,
+            Self::Original => &RuleSet::ORIGINAL,
+        }
+    }
+}
+
+impl Display for Patch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.rule_set().name.fmt(f)
+    }
+}
+
+/// An error for a string that is not a patch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePatchError(pub String);
+
+impl Display for ParsePatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid patch `{}`, expected live or original", self.0)
+    }
+}
+
+impl std::error::Error for ParsePatchError {}
+
+impl FromStr for Patch {
+    type Err = ParsePatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "live" => Ok(Self::Live),
+            "original" => Ok(Self::Original),
+            _ => Err(ParsePatchError(s.to_string())),
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::chance_format::Chance;
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::hook_escape_chance_settings::Tier;
+use crate::offering::Offering;
+use crate::rule_set::RuleSet;
+use crate::survivor::Perk;
+use std::fmt::Display;
+
+/// A single change to a loadout.
+///
+/// Survivor indexes start from 0.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Adjustment {
+    /// Equip a perk at tier I, or raise its tier
+    IncrementPerk { survivor: u8, perk: Perk },
+
+    /// Lower a perk's tier, or unequip it at tier I
+    DecrementPerk { survivor: u8, perk: Perk },
+
+    /// Burn a Vigo's Jar of Salty Lips
+    AddSaltyLips { survivor: u8 },
+
+    /// Remove a survivor's offering
+    RemoveOffering { survivor: u8 },
+
+    /// Kill a survivor
+    KillSurvivor { survivor: u8 },
+}
+
+impl Adjustment {
+    /// Apply this adjustment to settings
+    pub fn apply(self, settings: &mut HookEscapeChanceSettings) {
+        match self {
+            Self::IncrementPerk { survivor, perk } => {
+                if let Some(survivor) = settings.survivors.get_mut(usize::from(survivor)) {
+                    survivor.increment_perk_tier(perk);
+                }
+            }
+            Self::DecrementPerk { survivor, perk } => {
+                if let Some(survivor) = settings.survivors.get_mut(usize::from(survivor)) {
+                    survivor.decrement_perk_tier(perk);
+                }
+            }
+            Self::AddSaltyLips { survivor } => {
+                if let Some(survivor) = settings.survivors.get_mut(usize::from(survivor)) {
+                    survivor.offering = Some(Offering::VigosJarOfSaltyLips);
+                }
+            }
+            Self::RemoveOffering { survivor } => {
+                if let Some(survivor) = settings.survivors.get_mut(usize::from(survivor)) {
+                    survivor.offering = None;
+                }
+            }
+            Self::KillSurvivor { survivor } => {
+                if let Some(survivor) = settings.survivors.get_mut(usize::from(survivor)) {
+                    survivor.alive = false;
+                }
+            }
+        }
+    }
+}
+
+impl Display for Adjustment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IncrementPerk { survivor, perk } => {
+                write!(f, "Upgrade {} (Player {})", perk, survivor + 1)
+            }
+            Self::DecrementPerk { survivor, perk } => {
+                write!(f, "Downgrade {} (Player {})", perk, survivor + 1)
+            }
+            Self::AddSaltyLips { survivor } => {
+                write!(f, "Add Salty Lips (Player {})", survivor + 1)
+            }
+            Self::RemoveOffering { survivor } => {
+                write!(f, "Remove Offering (Player {})", survivor + 1)
+            }
+            Self::KillSurvivor { survivor } => write!(f, "Kill Player {}", survivor + 1),
+        }
+    }
+}
+
+/// How much one adjustment changes the escape chance
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sensitivity {
+    /// The change to the loadout
+    pub adjustment: Adjustment,
+
+    /// The escape chance after the adjustment
+    pub escape_chance: f64,
+
+    /// The change in escape chance
+    pub delta: f64,
+
+    /// The exact chance after the adjustment, for display with [`crate::chance_format::ChanceFormat::format_change`]
+    pub chance: Chance,
+}
+
+/// Get the adjustments worth considering for the given settings.
+///
+/// This covers each Slippery Meat tier step of the hooked survivor,
+/// each Up the Ante tier step of the alive survivors,
+/// one more Salty Lips, removing each offering, and one fewer alive teammate.
+pub fn adjustments(settings: &HookEscapeChanceSettings) -> Vec<Adjustment> {
+    let mut adjustments = Vec::new();
+    let hooked_survivor = settings.hooked_survivor;
+
+    for (index, survivor) in settings.survivors.iter().enumerate() {
+        let index = index as u8;
+        let perks: &[Perk] = if index == hooked_survivor {
+            &[Perk::SlipperyMeat, Perk::UpTheAnte]
+        } else if survivor.alive {
+            &[Perk::UpTheAnte]
+        } else {
+            continue;
+        };
+
+        for perk in perks.iter().copied() {
+            let tier = survivor.perk_tier(perk);
+            if tier != Some(Tier::III) {
+                adjustments.push(Adjustment::IncrementPerk {
+                    survivor: index,
+                    perk,
+                });
+            }
+            if tier.is_some() {
+                adjustments.push(Adjustment::DecrementPerk {
+                    survivor: index,
+                    perk,
+                });
+            }
+        }
+    }
+
+    if let Some(index) = settings
+        .survivors
+        .iter()
+        .position(|survivor| survivor.offering.is_none())
+    {
+        adjustments.push(Adjustment::AddSaltyLips {
+            survivor: index as u8,
+        });
+    }
+
+    for (index, survivor) in settings.survivors.iter().enumerate() {
+        if survivor.offering.is_some() {
+            adjustments.push(Adjustment::RemoveOffering {
+                survivor: index as u8,
+            });
+        }
+    }
+
+    if let Some(index) = settings
+        .survivors
+        .iter()
+        .enumerate()
+        .rposition(|(index, survivor)| survivor.alive && index != usize::from(hooked_survivor))
+    {
+        adjustments.push(Adjustment::KillSurvivor {
+            survivor: index as u8,
+        });
+    }
+
+    adjustments
+}
+
+/// Calculate how much each adjustment from [`adjustments`] changes the escape chance.
+pub fn analyze(settings: &HookEscapeChanceSettings, rules: &RuleSet) -> Vec<Sensitivity> {
+    let base = settings.calculate(rules);
+
+    adjustments(settings)
+        .into_iter()
+        .map(|adjustment| {
+            let mut adjusted = settings.clone();
+            adjustment.apply(&mut adjusted);
+            let escape_chance = adjusted.calculate(rules);
+
+            Sensitivity {
+                adjustment,
+                escape_chance,
+                delta: escape_chance - base,
+                chance: Chance::of(&adjusted, rules),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_settings() {
+        let sensitivities = analyze(&HookEscapeChanceSettings::new(), &RuleSet::LIVE);
+        let adjustments: Vec<_> = sensitivities.iter().map(|s| s.adjustment).collect();
+        assert_eq!(
+            adjustments,
+            vec![
+                Adjustment::IncrementPerk {
+                    survivor: 0,
+                    perk: Perk::SlipperyMeat
+                },
+                Adjustment::IncrementPerk {
+                    survivor: 0,
+                    perk: Perk::UpTheAnte
+                },
+                Adjustment::IncrementPerk {
+                    survivor: 1,
+                    perk: Perk::UpTheAnte
+                },
+                Adjustment::IncrementPerk {
+                    survivor: 2,
+                    perk: Perk::UpTheAnte
+                },
+                Adjustment::IncrementPerk {
+                    survivor: 3,
+                    perk: Perk::UpTheAnte
+                },
+                Adjustment::AddSaltyLips { survivor: 0 },
+                Adjustment::KillSurvivor { survivor: 3 },
+            ]
+        );
+
+        // Slippery Meat I: 6% over 6 attempts
+        assert!((sensitivities[0].escape_chance - 0.310_130_218_944).abs() < 0.00000000001);
+        // Up the Ante I with 3 other alive survivors: 7% over 3 attempts
+        assert!((sensitivities[1].delta - (0.195_643 - 0.115_264)).abs() < 0.00000000001);
+        // Nothing depends on the # of alive survivors
+        assert_eq!(sensitivities[6].delta, 0.0);
+    }
+
+    #[test]
+    fn killing_reduces_up_the_ante() {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[1].set_perk_tier(Perk::UpTheAnte, Some(Tier::III));
+
+        let sensitivities = analyze(&settings, &RuleSet::LIVE);
+        let kill = sensitivities
+            .iter()
+            .find(|s| s.adjustment == Adjustment::KillSurvivor { survivor: 3 })
+            .unwrap();
+        assert!(kill.delta < 0.0);
+
+        let downgrade = sensitivities
+            .iter()
+            .find(|s| {
+                s.adjustment
+                    == Adjustment::DecrementPerk {
+                        survivor: 1,
+                        perk: Perk::UpTheAnte,
+                    }
+            })
+            .unwrap();
+        assert!(downgrade.delta < 0.0);
+    }
+}
@@ -0,0 +1,339 @@
+//! On-disk formats for [`HookEscapeChanceSettings`].
+//!
+//! Every document has a `version` field.
+//! Older versions are migrated to the current model when loaded,
+//! and settings are always saved as the current version.
+//! Loading only checks the shape of a document;
+//! settings of every version are validated the same way, when they are used.
+//!
+//! # Versions
+//! 1. Individual survivors: `hooked_survivor` and `survivors`.
+
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::offering::Offering;
+use crate::survivor::EquippedPerk;
+use crate::survivor::Survivor;
+use crate::survivor::NUM_PERK_SLOTS;
+use serde::Deserialize;
+use serde::Serialize;
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+/// The schema version written by this version of the calculator
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A supported file format
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Format {
+    Json,
+    /// Only with the `toml` dependency, which the `preferences` feature enables
+    #[cfg(feature = "toml")]
+    Toml,
+}
+
+impl Format {
+    /// Guess the format from a file extension
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            #[cfg(feature = "toml")]
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// An error that occured while saving or loading settings
+#[derive(Debug)]
+pub enum Error {
+    Json(serde_json::Error),
+    #[cfg(feature = "toml")]
+    TomlSerialize(toml::ser::Error),
+    #[cfg(feature = "toml")]
+    TomlDeserialize(toml::de::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "invalid json settings: {}", e),
+            #[cfg(feature = "toml")]
+            Self::TomlSerialize(e) => write!(f, "failed to serialize settings as toml: {}", e),
+            #[cfg(feature = "toml")]
+            Self::TomlDeserialize(e) => write!(f, "invalid toml settings: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(e) => Some(e),
+            #[cfg(feature = "toml")]
+            Self::TomlSerialize(e) => Some(e),
+            #[cfg(feature = "toml")]
+            Self::TomlDeserialize(e) => Some(e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::ser::Error> for Error {
+    fn from(e: toml::ser::Error) -> Self {
+        Self::TomlSerialize(e)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Self::TomlDeserialize(e)
+    }
+}
+
+/// Save settings in the given format.
+pub fn serialize(settings: &HookEscapeChanceSettings, format: Format) -> Result<String, Error> {
+    match format {
+        Format::Json => Ok(serde_json::to_string_pretty(settings)?),
+        #[cfg(feature = "toml")]
+        Format::Toml => Ok(toml::to_string(settings)?),
+    }
+}
+
+/// Load settings of any supported version in the given format.
+pub fn deserialize(data: &str, format: Format) -> Result<HookEscapeChanceSettings, Error> {
+    match format {
+        Format::Json => Ok(serde_json::from_str(data)?),
+        #[cfg(feature = "toml")]
+        Format::Toml => Ok(toml::from_str(data)?),
+    }
+}
+
+/// The current schema
+#[derive(Debug, Serialize)]
+pub struct SettingsV1 {
+    version: u32,
+    hooked_survivor: u8,
+    survivors: Vec<SurvivorV1>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SurvivorV1 {
+    alive: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offering: Option<Offering>,
+    // Tables must come last in toml
+    #[serde(default)]
+    perks: Vec<EquippedPerk>,
+}
+
+impl From<HookEscapeChanceSettings> for SettingsV1 {
+    fn from(settings: HookEscapeChanceSettings) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            hooked_survivor: settings.hooked_survivor,
+            survivors: settings
+                .survivors
+                .iter()
+                .map(|survivor| SurvivorV1 {
+                    alive: survivor.alive,
+                    offering: survivor.offering,
+                    perks: survivor.perks.iter().flatten().copied().collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The fields of every schema version, before the version is checked
+#[derive(Debug, Deserialize)]
+pub struct RawSettings {
+    version: u32,
+
+    // Version 1
+    hooked_survivor: Option<u8>,
+    survivors: Option<Vec<SurvivorV1>>,
+}
+
+/// An error that occured while migrating settings to the current model
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The version is not supported
+    UnsupportedVersion(u32),
+
+    /// A field required by the version is missing
+    MissingField { version: u32, field: &'static str },
+
+    /// There were not exactly 4 survivors
+    InvalidNumSurvivors(usize),
+
+    /// A survivor had more perks than perk slots
+    TooManyPerks { survivor: usize, count: usize },
+}
+
+impl Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported settings version {}, the latest is {}",
+                version, SCHEMA_VERSION
+            ),
+            Self::MissingField { version, field } => write!(
+                f,
+                "missing field `{}`, required by version {}",
+                field, version
+            ),
+            Self::InvalidNumSurvivors(count) => {
+                write!(f, "expected 4 survivors, found {}", count)
+            }
+            Self::TooManyPerks { survivor, count } => write!(
+                f,
+                "survivor {} has {} perks, but only {} fit",
+                survivor, count, NUM_PERK_SLOTS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl TryFrom<RawSettings> for HookEscapeChanceSettings {
+    type Error = MigrationError;
+
+    fn try_from(raw: RawSettings) -> Result<Self, Self::Error> {
+        let version = raw.version;
+        let missing = |field| MigrationError::MissingField { version, field };
+
+        match version {
+            1 => {
+                let raw_survivors = raw.survivors.ok_or_else(|| missing("survivors"))?;
+                if raw_survivors.len() != 4 {
+                    return Err(MigrationError::InvalidNumSurvivors(raw_survivors.len()));
+                }
+
+                let mut settings = HookEscapeChanceSettings::new();
+                settings.hooked_survivor = raw
+                    .hooked_survivor
+                    .ok_or_else(|| missing("hooked_survivor"))?;
+                for (index, (survivor, raw_survivor)) in
+                    settings.survivors.iter_mut().zip(raw_survivors).enumerate()
+                {
+                    if raw_survivor.perks.len() > NUM_PERK_SLOTS {
+                        return Err(MigrationError::TooManyPerks {
+                            survivor: index,
+                            count: raw_survivor.perks.len(),
+                        });
+                    }
+
+                    let mut perks = [None; NUM_PERK_SLOTS];
+                    for (slot, perk) in perks.iter_mut().zip(raw_survivor.perks) {
+                        *slot = Some(perk);
+                    }
+
+                    *survivor = Survivor {
+                        alive: raw_survivor.alive,
+                        perks,
+                        offering: raw_survivor.offering,
+                    };
+                }
+
+                Ok(settings)
+            }
+            version => Err(MigrationError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hook_escape_chance_settings::Tier;
+    use crate::rule_set::RuleSet;
+    use crate::survivor::Perk;
+
+    fn example_settings() -> HookEscapeChanceSettings {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.hooked_survivor = 1;
+        settings.survivors[1].set_perk_tier(Perk::SlipperyMeat, Some(Tier::III));
+        settings.survivors[1].offering = Some(Offering::IvoryChalkPouch);
+        settings.survivors[2].set_perk_tier(Perk::UpTheAnte, Some(Tier::II));
+        settings.survivors[3].alive = false;
+        settings
+    }
+
+    #[test]
+    fn round_trip() {
+        let settings = example_settings();
+
+        let data = serialize(&settings, Format::Json).unwrap();
+        assert_eq!(deserialize(&data, Format::Json).unwrap(), settings);
+
+        #[cfg(feature = "toml")]
+        {
+            let data = serialize(&settings, Format::Toml).unwrap();
+            assert_eq!(deserialize(&data, Format::Toml).unwrap(), settings);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn toml_layout() {
+        let data = serialize(&example_settings(), Format::Toml).unwrap();
+        assert!(data.starts_with("version = 1\nhooked_survivor = 1\n"));
+        assert!(data.contains("offering = \"ivory_chalk_pouch\""));
+        assert!(data.contains("perk = \"slippery_meat\"\ntier = \"III\""));
+    }
+
+    #[test]
+    fn load_v1() {
+        let data = r#"{
+            "version": 1,
+            "hooked_survivor": 0,
+            "survivors": [
+                { "alive": true, "perks": [{ "perk": "slippery_meat", "tier": "III" }] },
+                { "alive": true, "perks": [{ "perk": "up_the_ante", "tier": "III" }] },
+                { "alive": true },
+                { "alive": true }
+            ]
+        }"#;
+        let settings = deserialize(data, Format::Json).unwrap();
+        let chance = settings.calculate(&RuleSet::LIVE);
+        assert!((chance - 0.673059626631).abs() < 0.00000000001);
+    }
+
+    #[test]
+    fn load_without_validation() {
+        let survivor = r#"{ "alive": true, "offering": "vigos_jar_of_salty_lips" }"#;
+        let data = format!(
+            r#"{{ "version": 1, "hooked_survivor": 0, "survivors": [{}] }}"#,
+            [survivor; 4].join(", ")
+        );
+        let settings = deserialize(&data, Format::Json).unwrap();
+        let chance = settings.calculate(&RuleSet::LIVE);
+        assert!((chance - 0.407296).abs() < 0.00000000001);
+
+        let survivor = r#"{ "alive": true }"#;
+        let data = format!(
+            r#"{{ "version": 1, "hooked_survivor": 4, "survivors": [{}] }}"#,
+            [survivor; 4].join(", ")
+        );
+        let settings = deserialize(&data, Format::Json).unwrap();
+        assert!(settings.try_calculate(&RuleSet::LIVE).is_err());
+    }
+
+    #[test]
+    fn bad_version() {
+        let error = deserialize(r#"{ "version": 2 }"#, Format::Json).unwrap_err();
+        assert!(error.to_string().contains("unsupported settings version 2"));
+
+        let error = deserialize(r#"{ "version": 1 }"#, Format::Json).unwrap_err();
+        assert!(error.to_string().contains("missing field `survivors`"));
+    }
+}
@@ -0,0 +1,246 @@
+//! A local HTTP API for escape chance queries.
+//!
+//! # Endpoints
+//! * `POST /calculate`: Calculate the escape chance of the settings in the body,
+//!   which use the same JSON format as saved settings, returning a [`Report`].
+//!   The optional `patch` query parameter picks the rules, like `/calculate?patch=live`.
+//! * `GET /rules`: The rule set of every patch.
+//!
+//! Errors are returned as JSON with an `error` message.
+//! Malformed requests, like bodies that aren't JSON, get a `400`.
+//! JSON that isn't settings, and settings that don't describe a legal trial, get a `422`.
+
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::report::Report;
+use crate::rule_set::Patch;
+use crate::rule_set::RuleSet;
+use serde::Deserialize;
+use serde::Serialize;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use warp::http::StatusCode;
+use warp::reply::Response;
+use warp::Filter;
+use warp::Rejection;
+use warp::Reply;
+
+/// The address the server binds to by default, which is only reachable from this machine
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:3000";
+
+/// The largest request body accepted, in bytes
+const MAX_BODY_SIZE: u64 = 64 * 1024;
+
+/// The query parameters of `POST /calculate`
+#[derive(Debug, Default, Deserialize)]
+struct CalculateQuery {
+    /// The patch to use the rules of
+    #[serde(default)]
+    patch: Patch,
+}
+
+/// An entry of the response of `GET /rules`
+#[derive(Debug, Serialize)]
+pub struct RulesEntry {
+    pub patch: Patch,
+    pub rules: &'static RuleSet,
+}
+
+/// The body of an error response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    /// What went wrong
+    pub error: String,
+
+    /// The individual validation issues, if the settings were invalid
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<String>,
+}
+
+/// Make a JSON error response.
+fn error_response(status: StatusCode, error: String, issues: Vec<String>) -> Response {
+    let body = warp::reply::json(&ErrorResponse { error, issues });
+    warp::reply::with_status(body, status).into_response()
+}
+
+fn calculate(query: CalculateQuery, body: serde_json::Value) -> Response {
+    let settings: HookEscapeChanceSettings = match serde_json::from_value(body) {
+        Ok(settings) => settings,
+        Err(e) => {
+            return error_response(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("invalid settings: {}", e),
+                Vec::new(),
+            )
+        }
+    };
+
+    match Report::new(&settings, query.patch) {
+        Ok(report) => warp::reply::json(&report).into_response(),
+        Err(e) => {
+            let issues = e.issues.iter().map(|issue| issue.to_string()).collect();
+            error_response(StatusCode::UNPROCESSABLE_ENTITY, e.to_string(), issues)
+        }
+    }
+}
+
+fn rules() -> Response {
+    let rules: Vec<_> = Patch::ALL
+        .iter()
+        .map(|patch| RulesEntry {
+            patch: *patch,
+            rules: patch.rule_set(),
+        })
+        .collect();
+
+    warp::reply::json(&rules).into_response()
+}
+
+/// Turn a rejected request into a JSON error.
+async fn handle_rejection(rejection: Rejection) -> Result<Response, Infallible> {
+    let (status, error) = if rejection.is_not_found() {
+        (StatusCode::NOT_FOUND, "not found".to_string())
+    } else if let Some(e) = rejection.find::<warp::filters::body::BodyDeserializeError>() {
+        (StatusCode::BAD_REQUEST, e.to_string())
+    } else if let Some(e) = rejection.find::<warp::reject::InvalidQuery>() {
+        (StatusCode::BAD_REQUEST, e.to_string())
+    } else if let Some(e) = rejection.find::<warp::reject::PayloadTooLarge>() {
+        (StatusCode::PAYLOAD_TOO_LARGE, e.to_string())
+    } else if let Some(e) = rejection.find::<warp::reject::LengthRequired>() {
+        (StatusCode::LENGTH_REQUIRED, e.to_string())
+    } else if let Some(e) = rejection.find::<warp::reject::UnsupportedMediaType>() {
+        (StatusCode::UNSUPPORTED_MEDIA_TYPE, e.to_string())
+    } else if let Some(e) = rejection.find::<warp::reject::MethodNotAllowed>() {
+        (StatusCode::METHOD_NOT_ALLOWED, e.to_string())
+    } else {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal server error".to_string(),
+        )
+    };
+
+    Ok(error_response(status, error, Vec::new()))
+}
+
+/// The routes of the API
+pub fn routes() -> impl Filter<Extract = (Response,), Error = Infallible> + Clone {
+    let calculate = warp::path("calculate")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::query::<CalculateQuery>())
+        .and(warp::body::content_length_limit(MAX_BODY_SIZE))
+        .and(warp::body::json())
+        .map(calculate);
+    let rules = warp::path("rules")
+        .and(warp::path::end())
+        .and(warp::get())
+        .map(rules);
+
+    calculate
+        .or(rules)
+        .unify()
+        .recover(handle_rejection)
+        .unify()
+}
+
+/// Serve the API on the given address until the process exits.
+pub async fn serve(address: SocketAddr) {
+    warp::serve(routes()).run(address).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hook_escape_chance_settings::Tier;
+
+    const DEFAULT_SETTINGS: &str = r#"{"version": 1, "hooked_survivor": 0, "survivors": [{"alive": true}, {"alive": true}, {"alive": true}, {"alive": true}]}"#;
+
+    async fn post_calculate(path: &str, body: &str) -> (StatusCode, Vec<u8>) {
+        let response = warp::test::request()
+            .method("POST")
+            .path(path)
+            .header("content-type", "application/json")
+            .body(body)
+            .reply(&routes())
+            .await;
+
+        (response.status(), response.body().to_vec())
+    }
+
+    #[tokio::test]
+    async fn calculate_settings() {
+        let settings = HookEscapeChanceSettings::from_counts(Some(Tier::III), &[], 2, 4).unwrap();
+        let body = serde_json::to_string(&settings).unwrap();
+
+        let (status, body) = post_calculate("/calculate", &body).await;
+        assert_eq!(status, StatusCode::OK);
+        let response: Report = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response.patch, Patch::Live);
+        assert!(
+            (response.escape_chance - settings.calculate(&RuleSet::LIVE)).abs() < 0.00000000001
+        );
+        assert_eq!(response.num_tries, 6);
+        assert_eq!(response.breakdown.len(), 3);
+        assert_eq!(response.breakdown[0].source, "Slippery Meat III");
+        assert_eq!(response.steps.len(), 10);
+
+        let (status, body) = post_calculate("/calculate?patch=live", DEFAULT_SETTINGS).await;
+        assert_eq!(status, StatusCode::OK);
+        let response: Report = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response.patch, Patch::Live);
+        assert!((response.escape_chance - 0.115264).abs() < 0.00000000001);
+    }
+
+    #[tokio::test]
+    async fn calculate_errors() {
+        let (status, body) = post_calculate("/calculate", "not json").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(serde_json::from_slice::<ErrorResponse>(&body).is_ok());
+
+        let (status, _) = post_calculate("/calculate?patch=ptb", DEFAULT_SETTINGS).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = post_calculate("/calculate", "{\"version\": 2}").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert!(response.error.contains("unsupported settings version 2"));
+
+        let (status, _) = post_calculate(
+            "/calculate",
+            "{\"version\": 1, \"hooked_survivor\": 0, \"survivors\": []}",
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[0].alive = false;
+        let body = serde_json::to_string(&settings).unwrap();
+        let (status, body) = post_calculate("/calculate", &body).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response.issues.len(), 1);
+
+        let response = warp::test::request()
+            .method("GET")
+            .path("/calculate")
+            .reply(&routes())
+            .await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn get_rules() {
+        let response = warp::test::request().path("/rules").reply(&routes()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let rules: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(rules[0]["patch"], "live");
+        assert_eq!(rules[0]["rules"]["base_num_tries"], 3);
+        assert_eq!(rules.as_array().unwrap().len(), Patch::ALL.len());
+
+        let response = warp::test::request()
+            .path("/missing")
+            .reply(&routes())
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::rule_set::RuleSet;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::fmt::Display;
+
+/// The z-score of a two-sided 95% confidence interval
+pub const Z_95: f64 = 1.959_963_984_540_054;
+
+/// An error for an escape chance that can't be sampled
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NonFiniteChanceError {
+    /// The attempt #, starting from 1
+    pub attempt: u8,
+
+    /// The escape chance of the attempt
+    pub escape_chance: f64,
+}
+
+impl Display for NonFiniteChanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the escape chance of attempt {} is {}, which is not a finite number",
+            self.attempt, self.escape_chance
+        )
+    }
+}
+
+impl std::error::Error for NonFiniteChanceError {}
+
+/// A Monte Carlo simulator of self-unhook attempts.
+///
+/// The rng is seeded explicitly, so the same seed always produces the same results.
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    rng: ChaCha8Rng,
+}
+
+impl Simulator {
+    /// Make a new simulator from a seed.
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Simulate the given # of hooks with the given settings.
+    ///
+    /// This fails if the rule set makes the escape chance NaN or infinite.
+    pub fn simulate(
+        &mut self,
+        settings: &HookEscapeChanceSettings,
+        rules: &RuleSet,
+        num_trials: u64,
+    ) -> Result<SimulationResult, NonFiniteChanceError> {
+        let distribution = settings.distribution(rules);
+
+        self.simulate_with(num_trials, distribution.num_tries, |_attempt| {
+            distribution.escape_chance_per_attempt
+        })
+    }
+
+    /// Simulate the given # of hooks, with a custom escape chance for each attempt.
+    ///
+    /// `escape_chance` is called with the attempt #, starting from 1.
+    /// Chances outside of 0 to 1 are clamped, and the simulation fails on the first NaN or infinite chance.
+    pub fn simulate_with<F>(
+        &mut self,
+        num_trials: u64,
+        num_tries: u8,
+        mut escape_chance: F,
+    ) -> Result<SimulationResult, NonFiniteChanceError>
+    where
+        F: FnMut(u8) -> f64,
+    {
+        let mut escapes_on_attempt = vec![0; usize::from(num_tries)];
+
+        for _ in 0..num_trials {
+            for attempt in 1..=num_tries {
+                let chance = escape_chance(attempt);
+                if !chance.is_finite() {
+                    return Err(NonFiniteChanceError {
+                        attempt,
+                        escape_chance: chance,
+                    });
+                }
+
+                if self.rng.gen_bool(num::clamp(chance, 0.0, 1.0)) {
+                    escapes_on_attempt[usize::from(attempt - 1)] += 1;
+                    break;
+                }
+            }
+        }
+
+        Ok(SimulationResult {
+            num_trials,
+            escapes_on_attempt,
+        })
+    }
+}
+
+/// The results of a simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationResult {
+    /// The # of simulated hooks
+    pub num_trials: u64,
+
+    /// The # of escapes on each attempt. Index 0 is the first attempt.
+    pub escapes_on_attempt: Vec<u64>,
+}
+
+impl SimulationResult {
+    /// The total # of escapes
+    pub fn num_escapes(&self) -> u64 {
+        self.escapes_on_attempt.iter().sum()
+    }
+
+    /// The empirical escape rate
+    pub fn escape_rate(&self) -> f64 {
+        if self.num_trials == 0 {
+            return 0.0;
+        }
+
+        self.num_escapes() as f64 / self.num_trials as f64
+    }
+
+    /// Get the Wilson score interval of the escape rate for the given z-score, like [`Z_95`].
+    ///
+    /// Returns the lower and upper bound.
+    pub fn confidence_interval(&self, z: f64) -> (f64, f64) {
+        if self.num_trials == 0 {
+            return (0.0, 1.0);
+        }
+
+        let n = self.num_trials as f64;
+        let p = self.escape_rate();
+        let z2 = z * z;
+
+        let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = (z / (1.0 + z2 / n)) * ((p * (1.0 - p) / n) + (z2 / (4.0 * n * n))).sqrt();
+
+        ((center - margin).max(0.0), (center + margin).min(1.0))
+    }
+
+    /// Check whether the given probability lies in the confidence interval for the given z-score.
+    pub fn is_consistent_with(&self, probability: f64, z: f64) -> bool {
+        let (low, high) = self.confidence_interval(z);
+        (low..=high).contains(&probability)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hook_escape_chance_settings::Tier;
+    use crate::offering::Offering;
+    use crate::survivor::Perk;
+
+    const NUM_TRIALS: u64 = 100_000;
+
+    #[test]
+    fn deterministic() {
+        let settings = HookEscapeChanceSettings::new();
+        let a = Simulator::from_seed(42)
+            .simulate(&settings, &RuleSet::LIVE, 1_000)
+            .unwrap();
+        let b = Simulator::from_seed(42)
+            .simulate(&settings, &RuleSet::LIVE, 1_000)
+            .unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn matches_calculate() {
+        let mut simulator = Simulator::from_seed(0);
+
+        let mut settings = HookEscapeChanceSettings::new();
+        let result = simulator
+            .simulate(&settings, &RuleSet::LIVE, NUM_TRIALS)
+            .unwrap();
+        assert!(result.is_consistent_with(settings.calculate(&RuleSet::LIVE), Z_95));
+
+        settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, Some(Tier::III));
+        settings.survivors[1].set_perk_tier(Perk::UpTheAnte, Some(Tier::III));
+        let result = simulator
+            .simulate(&settings, &RuleSet::LIVE, NUM_TRIALS)
+            .unwrap();
+        assert!(result.is_consistent_with(settings.calculate(&RuleSet::LIVE), Z_95));
+
+        for survivor in settings.survivors.iter_mut() {
+            survivor.offering = Some(Offering::VigosJarOfSaltyLips);
+        }
+        let result = simulator
+            .simulate(&settings, &RuleSet::LIVE, NUM_TRIALS)
+            .unwrap();
+        assert!(result.is_consistent_with(settings.calculate(&RuleSet::LIVE), Z_95));
+    }
+
+    #[test]
+    fn non_finite_chance() {
+        let mut simulator = Simulator::from_seed(0);
+
+        let error = simulator
+            .simulate_with(10, 3, |attempt| if attempt == 2 { f64::NAN } else { 0.0 })
+            .unwrap_err();
+        assert_eq!(error.attempt, 2);
+        assert!(error.escape_chance.is_nan());
+
+        assert!(simulator
+            .simulate_with(10, 3, |_attempt| f64::INFINITY)
+            .is_err());
+        let result = simulator.simulate_with(10, 3, |_attempt| 2.0).unwrap();
+        assert_eq!(result.num_escapes(), 10);
+    }
+}
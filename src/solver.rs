@@ -0,0 +1,245 @@
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::hook_escape_chance_settings::Tier;
+use crate::offering::Offering;
+use crate::offering::OfferingTarget;
+use crate::rule_set::RuleSet;
+use crate::survivor::Perk;
+use crate::survivor::Survivor;
+use std::cmp::Reverse;
+
+const TIERS: &[Option<Tier>] = &[None, Some(Tier::I), Some(Tier::II), Some(Tier::III)];
+
+/// How much of a loadout is spent
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Cost {
+    /// The # of perk slots and offerings used
+    pub num_slots: u8,
+
+    /// The sum of all perk tiers, counting tier I as 1
+    pub num_tier_levels: u8,
+}
+
+impl Cost {
+    /// Get the cost of the given settings
+    pub fn of(settings: &HookEscapeChanceSettings) -> Self {
+        let mut cost = Self {
+            num_slots: 0,
+            num_tier_levels: 0,
+        };
+
+        for survivor in settings.survivors.iter() {
+            for equipped in survivor.perks.iter().flatten() {
+                cost.num_slots += 1;
+                cost.num_tier_levels += equipped.tier.index() as u8 + 1;
+            }
+
+            if survivor.offering.is_some() {
+                cost.num_slots += 1;
+            }
+        }
+
+        cost
+    }
+}
+
+/// A loadout that reaches the target escape chance
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    /// The loadout. Player 1 is hooked.
+    pub settings: HookEscapeChanceSettings,
+
+    /// The escape chance of the loadout
+    pub escape_chance: f64,
+
+    /// The cost of the loadout
+    pub cost: Cost,
+}
+
+/// Find the minimal loadouts that reach the target escape chance.
+///
+/// A loadout is minimal if removing any perk or offering, or lowering any perk tier, puts it below the target.
+/// Player 1 is always the hooked survivor, and dead survivors come last.
+/// Teammates are interchangeable, so only one ordering of each group of teammates is returned.
+///
+/// Results are sorted by cost, then by # of alive survivors and escape chance, both descending.
+pub fn solve(target: f64, rules: &RuleSet) -> Vec<Solution> {
+    let hooked_options: Vec<Survivor> = TIERS
+        .iter()
+        .flat_map(|slippery_meat| {
+            TIERS.iter().flat_map(move |up_the_ante| {
+                std::iter::once(None)
+                    .chain(Offering::ALL.iter().copied().map(Some))
+                    .map(move |offering| {
+                        let mut survivor = Survivor::new();
+                        survivor.set_perk_tier(Perk::SlipperyMeat, *slippery_meat);
+                        survivor.set_perk_tier(Perk::UpTheAnte, *up_the_ante);
+                        survivor.offering = offering;
+                        survivor
+                    })
+            })
+        })
+        .collect();
+    let team_offerings: Vec<Option<Offering>> = std::iter::once(None)
+        .chain(
+            Offering::ALL
+                .iter()
+                .copied()
+                .filter(|offering| offering.target() == OfferingTarget::Team)
+                .map(Some),
+        )
+        .collect();
+    let alive_teammate_options: Vec<Survivor> = TIERS
+        .iter()
+        .flat_map(|up_the_ante| {
+            team_offerings.iter().map(move |offering| {
+                let mut survivor = Survivor::new();
+                survivor.set_perk_tier(Perk::UpTheAnte, *up_the_ante);
+                survivor.offering = *offering;
+                survivor
+            })
+        })
+        .collect();
+    let dead_teammate_options: Vec<Survivor> = team_offerings
+        .iter()
+        .map(|offering| {
+            let mut survivor = Survivor::new();
+            survivor.alive = false;
+            survivor.offering = *offering;
+            survivor
+        })
+        .collect();
+
+    let mut solutions = Vec::new();
+    for num_alive_teammates in 0..=3 {
+        let alive_teammates = multisets(&alive_teammate_options, num_alive_teammates);
+        let dead_teammates = multisets(&dead_teammate_options, 3 - num_alive_teammates);
+
+        for hooked in hooked_options.iter() {
+            for alive in alive_teammates.iter() {
+                for dead in dead_teammates.iter() {
+                    let mut settings = HookEscapeChanceSettings::new();
+                    settings.survivors[0] = *hooked;
+                    for (slot, survivor) in settings.survivors[1..]
+                        .iter_mut()
+                        .zip(alive.iter().chain(dead.iter()))
+                    {
+                        *slot = *survivor;
+                    }
+
+                    let escape_chance = settings.calculate(rules);
+                    if escape_chance >= target && is_minimal(&settings, target, rules) {
+                        solutions.push(Solution {
+                            cost: Cost::of(&settings),
+                            settings,
+                            escape_chance,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    solutions.sort_by(|a, b| {
+        a.cost
+            .cmp(&b.cost)
+            .then_with(|| {
+                Reverse(a.settings.num_alive_survivors())
+                    .cmp(&Reverse(b.settings.num_alive_survivors()))
+            })
+            .then_with(|| b.escape_chance.total_cmp(&a.escape_chance))
+    });
+
+    solutions
+}
+
+/// Check that no single reduction of the loadout still reaches the target.
+fn is_minimal(settings: &HookEscapeChanceSettings, target: f64, rules: &RuleSet) -> bool {
+    for index in 0..settings.survivors.len() {
+        let survivor = &settings.survivors[index];
+
+        for equipped in survivor.perks.iter().flatten() {
+            let mut reduced = settings.clone();
+            reduced.survivors[index].decrement_perk_tier(equipped.perk);
+            if reduced.calculate(rules) >= target {
+                return false;
+            }
+        }
+
+        if survivor.offering.is_some() {
+            let mut reduced = settings.clone();
+            reduced.survivors[index].offering = None;
+            if reduced.calculate(rules) >= target {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// Get every multiset of the given size from the options, as non-decreasing index sequences.
+pub fn multisets<T: Copy>(options: &[T], size: usize) -> Vec<Vec<T>> {
+    fn recurse<T: Copy>(
+        options: &[T],
+        size: usize,
+        start: usize,
+        current: &mut Vec<T>,
+        ret: &mut Vec<Vec<T>>,
+    ) {
+        if current.len() == size {
+            ret.push(current.clone());
+            return;
+        }
+
+        for index in start..options.len() {
+            current.push(options[index]);
+            recurse(options, size, index, current, ret);
+            current.pop();
+        }
+    }
+
+    let mut ret = Vec::new();
+    recurse(options, size, 0, &mut Vec::with_capacity(size), &mut ret);
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_needs_nothing() {
+        let solutions = solve(0.1, &RuleSet::LIVE);
+        assert_eq!(solutions[0].cost.num_slots, 0);
+        assert_eq!(solutions[0].settings, HookEscapeChanceSettings::new());
+    }
+
+    #[test]
+    fn fifty_percent() {
+        let solutions = solve(0.5, &RuleSet::LIVE);
+        assert!(!solutions.is_empty());
+        assert!(solutions
+            .iter()
+            .all(|solution| solution.escape_chance >= 0.5
+                && solution
+                    .settings
+                    .validate()
+                    .iter()
+                    .all(|issue| !issue.is_error())));
+        assert!(solutions
+            .windows(2)
+            .all(|pair| pair[0].cost <= pair[1].cost));
+
+        let cheapest = &solutions[0];
+        assert_eq!(cheapest.cost.num_slots, 2);
+        assert_eq!(cheapest.cost.num_tier_levels, 3);
+        assert!(cheapest.settings.survivors[0]
+            .perk_tier(Perk::SlipperyMeat)
+            .is_some());
+    }
+
+    #[test]
+    fn impossible() {
+        assert!(solve(1.0, &RuleSet::LIVE).is_empty());
+    }
+}
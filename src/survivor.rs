@@ -0,0 +1,129 @@
+use crate::hook_escape_chance_settings::Tier;
+use crate::offering::Offering;
+use std::fmt::Display;
+
+/// The # of perk slots each survivor has
+pub const NUM_PERK_SLOTS: usize = 4;
+
+/// A perk that affects hook escapes
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Perk {
+    SlipperyMeat,
+    UpTheAnte,
+}
+
+impl Perk {
+    /// The in-game name of this perk
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SlipperyMeat => "Slippery Meat",
+            Self::UpTheAnte => "Up the Ante",
+        }
+    }
+}
+
+impl Display for Perk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.name().fmt(f)
+    }
+}
+
+/// A perk in a perk slot
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct EquippedPerk {
+    pub perk: Perk,
+    pub tier: Tier,
+}
+
+/// A survivor in the trial
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Survivor {
+    /// Whether this survivor is still alive
+    pub alive: bool,
+
+    /// This survivor's perk slots
+    pub perks: [Option<EquippedPerk>; NUM_PERK_SLOTS],
+
+    /// The luck offering this survivor burned
+    pub offering: Option<Offering>,
+}
+
+impl Survivor {
+    /// Make a new alive survivor with no perks or offering.
+    pub fn new() -> Self {
+        Self {
+            alive: true,
+            perks: [None; NUM_PERK_SLOTS],
+            offering: None,
+        }
+    }
+
+    /// Get the tier of the given perk, if this survivor has it.
+    ///
+    /// If the perk is in more than one slot, the first one is used.
+    pub fn perk_tier(&self, perk: Perk) -> Option<Tier> {
+        self.perks
+            .iter()
+            .flatten()
+            .find(|equipped| equipped.perk == perk)
+            .map(|equipped| equipped.tier)
+    }
+
+    /// Set the tier of the given perk, equipping or unequipping it as needed.
+    ///
+    /// Returns false if the perk could not be equipped because all slots are full.
+    pub fn set_perk_tier(&mut self, perk: Perk, tier: Option<Tier>) -> bool {
+        let slot = self
+            .perks
+            .iter()
+            .position(|slot| slot.is_some_and(|equipped| equipped.perk == perk));
+
+        match (slot, tier) {
+            (Some(slot), Some(tier)) => {
+                self.perks[slot] = Some(EquippedPerk { perk, tier });
+                true
+            }
+            (Some(slot), None) => {
+                self.perks[slot] = None;
+                true
+            }
+            (None, Some(tier)) => match self.perks.iter_mut().find(|slot| slot.is_none()) {
+                Some(slot) => {
+                    *slot = Some(EquippedPerk { perk, tier });
+                    true
+                }
+                None => false,
+            },
+            (None, None) => true,
+        }
+    }
+
+    /// Increase the tier of the given perk, equipping it at tier I if needed.
+    pub fn increment_perk_tier(&mut self, perk: Perk) {
+        let tier = match self.perk_tier(perk) {
+            None => Some(Tier::I),
+            Some(Tier::I) => Some(Tier::II),
+            Some(Tier::II) => Some(Tier::III),
+            Some(Tier::III) => Some(Tier::III),
+        };
+        self.set_perk_tier(perk, tier);
+    }
+
+    /// Decrease the tier of the given perk, unequipping it below tier I.
+    pub fn decrement_perk_tier(&mut self, perk: Perk) {
+        let tier = match self.perk_tier(perk) {
+            None => None,
+            Some(Tier::I) => None,
+            Some(Tier::II) => Some(Tier::I),
+            Some(Tier::III) => Some(Tier::II),
+        };
+        self.set_perk_tier(perk, tier);
+    }
+}
+
+impl Default for Survivor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,463 @@
+//! The table of every legal scenario.
+//!
+//! A scenario is the hooked survivor's Slippery Meat tier, Up the Ante tier and offering,
+//! plus the loadout of each teammate: alive or dead, an Up the Ante tier if alive, and an offering.
+//! Teammates are interchangeable, so reorderings of the same teammates are one scenario.
+//! Loadouts that would have validation warnings are never generated,
+//! since each one calculates the same as the loadout without the item that has no effect:
+//! Slippery Meat and chalk pouches only help the survivor holding them,
+//! and Up the Ante only works while its holder is alive.
+//! Scenarios with the same exact per-attempt escape chance and # of attempts share an equivalence class.
+//! [`generate`] makes one row per equivalence class, with its cheapest scenario as an example,
+//! and [`generate_loadouts`] makes one row per scenario, so specific loadouts can be looked up.
+//! Chances come from [`HookEscapeChanceSettings::calculate`] and are displayed with [`Chance::of`], like the GUI.
+
+use crate::build_code;
+use crate::chance_format::Chance;
+use crate::chance_format::ChanceFormat;
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::hook_escape_chance_settings::Tier;
+use crate::offering::Offering;
+use crate::rule_set::RuleSet;
+use crate::solver::multisets;
+use crate::solver::Cost;
+use crate::survivor::Perk;
+use crate::survivor::Survivor;
+use num::BigRational;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// A row of the table
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRow {
+    /// The scenario, with survivor 1 on the hook.
+    ///
+    /// Rows for a whole equivalence class have its cheapest scenario, by [`Cost`].
+    pub settings: HookEscapeChanceSettings,
+
+    /// The chance of escaping on a single attempt
+    pub escape_chance_per_attempt: f64,
+
+    /// The # of self-unhook attempts
+    pub num_tries: u8,
+
+    /// The chance of escaping on any attempt
+    pub escape_chance: f64,
+
+    /// The exact escape chance, for display
+    pub chance: Chance,
+
+    /// The id shared by every scenario with the same exact per-attempt escape chance and # of attempts.
+    ///
+    /// Classes are numbered from 0 in the order of the table, so the best class is 0.
+    pub equivalence_class: usize,
+
+    /// The # of scenarios in the equivalence class
+    pub num_loadouts: usize,
+}
+
+impl TableRow {
+    /// The Slippery Meat tier of the hooked survivor
+    pub fn slippery_meat(&self) -> Option<Tier> {
+        self.settings
+            .hooked()
+            .and_then(|survivor| survivor.perk_tier(Perk::SlipperyMeat))
+    }
+
+    /// The Up the Ante tiers, starting with the hooked survivor's
+    pub fn up_the_ante(&self) -> Vec<Tier> {
+        self.settings
+            .hooked()
+            .into_iter()
+            .chain(self.settings.teammates())
+            .filter_map(|survivor| survivor.perk_tier(Perk::UpTheAnte))
+            .collect()
+    }
+
+    /// The offerings burned, starting with the hooked survivor's
+    pub fn offerings(&self) -> Vec<Offering> {
+        self.settings
+            .hooked()
+            .into_iter()
+            .chain(self.settings.teammates())
+            .filter_map(|survivor| survivor.offering)
+            .collect()
+    }
+
+    /// The # of alive survivors
+    pub fn alive(&self) -> u8 {
+        self.settings.num_alive_survivors()
+    }
+
+    /// The build code of [`Self::settings`]
+    pub fn build_code(&self) -> String {
+        build_code::encode(&self.settings)
+    }
+}
+
+/// Every loadout a teammate can have without validation warnings
+fn teammate_loadouts() -> Vec<Survivor> {
+    let offerings = [
+        None,
+        Some(Offering::SaltPouch),
+        Some(Offering::BlackSaltStatuette),
+        Some(Offering::VigosJarOfSaltyLips),
+    ];
+    let up_the_ante_tiers = [None, Some(Tier::I), Some(Tier::II), Some(Tier::III)];
+
+    let mut loadouts = Vec::new();
+    for alive in [true, false].iter().copied() {
+        for up_the_ante in up_the_ante_tiers.iter().copied() {
+            if !alive && up_the_ante.is_some() {
+                continue;
+            }
+
+            for offering in offerings.iter().copied() {
+                let mut survivor = Survivor::new();
+                survivor.alive = alive;
+                survivor.set_perk_tier(Perk::UpTheAnte, up_the_ante);
+                survivor.offering = offering;
+                loadouts.push(survivor);
+            }
+        }
+    }
+
+    loadouts
+}
+
+/// Every scenario, in a fixed order
+fn scenarios() -> Vec<HookEscapeChanceSettings> {
+    let tiers = [None, Some(Tier::I), Some(Tier::II), Some(Tier::III)];
+    let hooked_offerings: Vec<Option<Offering>> = std::iter::once(None)
+        .chain(Offering::ALL.iter().copied().map(Some))
+        .collect();
+    let teams = multisets(&teammate_loadouts(), 3);
+
+    let mut scenarios = Vec::new();
+    for slippery_meat in tiers.iter().copied() {
+        for up_the_ante in tiers.iter().copied() {
+            for offering in hooked_offerings.iter().copied() {
+                for team in teams.iter() {
+                    let mut settings = HookEscapeChanceSettings::new();
+                    settings.survivors[0].set_perk_tier(Perk::SlipperyMeat, slippery_meat);
+                    settings.survivors[0].set_perk_tier(Perk::UpTheAnte, up_the_ante);
+                    settings.survivors[0].offering = offering;
+                    settings.survivors[1..].copy_from_slice(team);
+                    scenarios.push(settings);
+                }
+            }
+        }
+    }
+
+    scenarios
+}
+
+/// Generate the table with one row per scenario, sorted by escape chance, descending.
+///
+/// Scenarios with the same escape chance keep the order they were generated in.
+pub fn generate_loadouts(rules: &RuleSet) -> Vec<TableRow> {
+    let mut classes: Vec<(Chance, Vec<HookEscapeChanceSettings>)> = Vec::new();
+    let mut indexes: HashMap<(BigRational, u8), usize> = HashMap::new();
+    for settings in scenarios() {
+        let key = settings.exact_distribution(rules);
+        let index = *indexes.entry(key).or_insert_with(|| {
+            classes.push((Chance::of(&settings, rules), Vec::new()));
+            classes.len() - 1
+        });
+        classes[index].1.push(settings);
+    }
+
+    classes.sort_by(|(a, _), (b, _)| b.escape_chance.cmp(&a.escape_chance));
+
+    let mut rows = Vec::new();
+    for (equivalence_class, (chance, scenarios)) in classes.into_iter().enumerate() {
+        let num_loadouts = scenarios.len();
+        for settings in scenarios {
+            let distribution = settings.distribution(rules);
+            rows.push(TableRow {
+                escape_chance_per_attempt: distribution.escape_chance_per_attempt,
+                num_tries: distribution.num_tries,
+                escape_chance: settings.calculate(rules),
+                settings,
+                chance: chance.clone(),
+                equivalence_class,
+                num_loadouts,
+            });
+        }
+    }
+
+    rows
+}
+
+/// Keep one row per equivalence class of a table from [`generate_loadouts`], with the cheapest scenario of the class.
+pub fn equivalence_classes(rows: Vec<TableRow>) -> Vec<TableRow> {
+    let mut classes: Vec<TableRow> = Vec::new();
+    for row in rows {
+        match classes.get_mut(row.equivalence_class) {
+            Some(class) => {
+                if Cost::of(&row.settings) < Cost::of(&class.settings) {
+                    *class = row;
+                }
+            }
+            None => classes.push(row),
+        }
+    }
+
+    classes
+}
+
+/// Generate the table with one row per equivalence class, sorted by escape chance, descending.
+pub fn generate(rules: &RuleSet) -> Vec<TableRow> {
+    equivalence_classes(generate_loadouts(rules))
+}
+
+fn format_tier(tier: Option<Tier>) -> String {
+    tier.map_or_else(String::new, |tier| tier.to_string())
+}
+
+fn format_tiers(tiers: &[Tier]) -> String {
+    tiers
+        .iter()
+        .map(|tier| tier.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn format_offerings(offerings: &[Offering]) -> String {
+    offerings
+        .iter()
+        .map(|offering| offering.name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Write the table as CSV.
+///
+/// Chances are written with [`ChanceFormat::format_probability`].
+/// The `build_code` column can be decoded with [`crate::build_code::decode`] to get the settings of a row.
+pub fn write_csv<W: Write>(
+    rows: &[TableRow],
+    format: &ChanceFormat,
+    writer: W,
+) -> Result<(), csv::Error> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record([
+        "slippery_meat",
+        "up_the_ante",
+        "offerings",
+        "alive",
+        "build_code",
+        "escape_chance_per_attempt",
+        "num_tries",
+        "escape_chance",
+        "equivalence_class",
+        "num_loadouts",
+    ])?;
+
+    for row in rows {
+        writer.write_record([
+            format_tier(row.slippery_meat()),
+            format_tiers(&row.up_the_ante()),
+            format_offerings(&row.offerings()),
+            row.alive().to_string(),
+            row.build_code(),
+            format.format_probability(&row.chance.escape_chance_per_attempt),
+            row.num_tries.to_string(),
+            format.format_probability(&row.chance.escape_chance),
+            row.equivalence_class.to_string(),
+            row.num_loadouts.to_string(),
+        ])?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Write the table as a Markdown table.
+///
+/// Chances are written with [`ChanceFormat::format_probability`], like [`write_csv`].
+pub fn write_markdown<W: Write>(
+    rows: &[TableRow],
+    format: &ChanceFormat,
+    mut writer: W,
+) -> std::io::Result<()> {
+    writeln!(
+        writer,
+        "| Slippery Meat | Up the Ante | Offerings | Alive | Per Attempt | Attempts | Escape Chance | Equivalence Class | Loadouts | Build Code |"
+    )?;
+    writeln!(writer, "|---|---|---|---|--:|--:|--:|--:|--:|---|")?;
+
+    for row in rows {
+        writeln!(
+            writer,
+            "| {} | {} | {} | {} | {} | {} | {} | {} | {} | `{}` |",
+            format_tier(row.slippery_meat()),
+            format_tiers(&row.up_the_ante()),
+            format_offerings(&row.offerings()),
+            row.alive(),
+            format.format_probability(&row.chance.escape_chance_per_attempt),
+            row.num_tries,
+            format.format_probability(&row.chance.escape_chance),
+            row.equivalence_class,
+            row.num_loadouts,
+            row.build_code()
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chance_format::ChanceStyle;
+    use num::ToPrimitive;
+
+    #[test]
+    fn generate_table() {
+        let rows = generate_loadouts(&RuleSet::LIVE);
+        assert!(rows
+            .windows(2)
+            .all(|pair| pair[0].chance.escape_chance >= pair[1].chance.escape_chance));
+
+        // 4 Slippery Meat tiers * 4 hooked Up the Ante tiers * 7 hooked offerings
+        // * 1540 multisets of 3 of the 20 teammate loadouts
+        assert_eq!(rows.len(), 172_480);
+        assert!(rows.iter().all(|row| row.settings.validate().is_empty()));
+
+        // Teammate reorderings are one row
+        let mut build_codes: Vec<_> = rows.iter().map(TableRow::build_code).collect();
+        build_codes.sort();
+        build_codes.dedup();
+        assert_eq!(build_codes.len(), rows.len());
+
+        // Specific loadouts can be looked up
+        let loadout = rows
+            .iter()
+            .find(|row| {
+                row.slippery_meat() == Some(Tier::II)
+                    && row.up_the_ante().is_empty()
+                    && row.offerings() == [Offering::VigosJarOfSaltyLips; 2]
+                    && row.alive() == 4
+            })
+            .unwrap();
+        assert!((loadout.escape_chance_per_attempt - 0.13).abs() < 0.00000000001);
+        assert_eq!(loadout.num_tries, 6);
+
+        // Alive survivors don't matter without Up the Ante
+        let default = rows
+            .iter()
+            .find(|row| row.settings == HookEscapeChanceSettings::new())
+            .unwrap();
+        assert!((default.escape_chance - 0.115264).abs() < 0.00000000001);
+        let mut dead_teammates = HookEscapeChanceSettings::new();
+        dead_teammates.survivors[3].alive = false;
+        let dead_teammates = rows
+            .iter()
+            .find(|row| row.settings == dead_teammates)
+            .unwrap();
+        assert_eq!(dead_teammates.equivalence_class, default.equivalence_class);
+
+        // Classes are shared by exactly the rows with the same distribution
+        let mut classes: HashMap<usize, ((BigRational, u8), usize)> = HashMap::new();
+        for row in rows.iter() {
+            let key = (row.chance.escape_chance_per_attempt.clone(), row.num_tries);
+            let (class_key, num_loadouts) = classes
+                .entry(row.equivalence_class)
+                .or_insert_with(|| (key.clone(), 0));
+            assert_eq!(class_key, &key);
+            *num_loadouts += 1;
+        }
+        let mut keys: Vec<_> = classes.values().map(|(key, _)| key).collect();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), classes.len());
+        assert_eq!(rows[0].equivalence_class, 0);
+        assert!(rows
+            .iter()
+            .all(|row| row.num_loadouts == classes[&row.equivalence_class].1));
+
+        // Chances are what calculate() gives, and the exact chance agrees with it
+        for row in rows.iter().step_by(97) {
+            assert_eq!(row.escape_chance, row.settings.calculate(&RuleSet::LIVE));
+            assert!(
+                (row.chance.escape_chance.to_f64().unwrap() - row.escape_chance).abs()
+                    < 0.00000000001
+            );
+        }
+
+        for row in rows.iter().take(20) {
+            let settings = build_code::decode(&row.build_code()).unwrap();
+            assert!((settings.calculate(&RuleSet::LIVE) - row.escape_chance).abs() < 0.00000000001);
+        }
+
+        // One row per class, with the cheapest loadout of the class
+        let class_rows = equivalence_classes(rows.clone());
+        assert_eq!(class_rows.len(), classes.len());
+        assert_eq!(
+            class_rows.iter().map(|row| row.num_loadouts).sum::<usize>(),
+            rows.len()
+        );
+        for (index, class_row) in class_rows.iter().enumerate() {
+            assert_eq!(class_row.equivalence_class, index);
+            assert_eq!(
+                class_row.escape_chance,
+                class_row.settings.calculate(&RuleSet::LIVE)
+            );
+        }
+        let default_class = &class_rows[default.equivalence_class];
+        assert_eq!(default_class.settings, HookEscapeChanceSettings::new());
+        for row in rows.iter() {
+            let class_row = &class_rows[row.equivalence_class];
+            assert_eq!(class_row.chance, row.chance);
+            assert!(Cost::of(&class_row.settings) <= Cost::of(&row.settings));
+        }
+    }
+
+    #[test]
+    fn export() {
+        let rows = &generate(&RuleSet::LIVE);
+
+        let mut csv = Vec::new();
+        write_csv(rows, &ChanceFormat::default(), &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), rows.len() + 1);
+        assert!(csv.starts_with("slippery_meat,up_the_ante,offerings,alive,build_code,"));
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with(",56.00%,6,99.27%,0,2"));
+        assert!(csv
+            .lines()
+            .nth(3)
+            .unwrap()
+            .ends_with(",54.00%,6,99.05%,2,14"));
+
+        let mut markdown = Vec::new();
+        write_markdown(rows, &ChanceFormat::default(), &mut markdown).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        assert_eq!(markdown.lines().count(), rows.len() + 2);
+        assert!(markdown.lines().nth(2).unwrap().starts_with(
+            "| III | III,III,III,III | Ivory Chalk Pouch, Vigo's Jar of Salty Lips, Vigo's Jar of Salty Lips, Vigo's Jar of Salty Lips | 4 | 56.00% | 6 | 99.27% | 0 | 2 |"
+        ));
+
+        // Both writers format chances the same way, even in styles for whole scenarios
+        let format = ChanceFormat {
+            style: ChanceStyle::PerAttempt,
+            decimals: 1,
+        };
+        let mut csv = Vec::new();
+        write_csv(&rows[..1], &format, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.lines().nth(1).unwrap().ends_with(",56.0%,6,99.3%,0,2"));
+        let mut markdown = Vec::new();
+        write_markdown(&rows[..1], &format, &mut markdown).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        assert!(markdown
+            .lines()
+            .nth(2)
+            .unwrap()
+            .contains("| 4 | 56.0% | 6 | 99.3% | 0 |"));
+    }
+}
@@ -0,0 +1,334 @@
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::offering::Offering;
+use crate::offering::OfferingTarget;
+use crate::survivor::Perk;
+use std::fmt::Display;
+
+/// How serious a validation issue is
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The settings are legal, but part of the loadout has no effect
+    Warning,
+
+    /// The settings do not describe a legal trial, so the result would be wrong
+    Error,
+}
+
+/// A problem with a loadout.
+///
+/// Survivor indexes start from 0.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ValidationIssue {
+    /// The hooked survivor index does not refer to a survivor
+    InvalidHookedSurvivor { index: u8 },
+
+    /// The hooked survivor is dead
+    HookedSurvivorDead { survivor: u8 },
+
+    /// A survivor has the same perk in more than one slot
+    DuplicatePerk { survivor: u8, perk: Perk },
+
+    /// More copies of a perk were requested than there are survivors
+    TooManyPerks { perk: Perk, count: usize },
+
+    /// More offerings were requested than there are survivors
+    TooManyOfferings { count: u8 },
+
+    /// The # of alive survivors is not between 1 and 4
+    InvalidNumAliveSurvivors { count: u8 },
+
+    /// More copies of Up the Ante were requested than there are alive survivors to hold them
+    TooManyUpTheAnte {
+        count: usize,
+        num_alive_survivors: u8,
+    },
+
+    /// A dead survivor has Up the Ante, which does nothing
+    UpTheAnteOnDeadSurvivor { survivor: u8 },
+
+    /// A survivor other than the hooked survivor has Slippery Meat, which does nothing
+    SlipperyMeatOnOtherSurvivor { survivor: u8 },
+
+    /// A survivor other than the hooked survivor burned an offering that only helps the burner
+    BurnerOfferingOnOtherSurvivor { survivor: u8, offering: Offering },
+}
+
+impl ValidationIssue {
+    /// How serious this issue is
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::InvalidHookedSurvivor { .. }
+            | Self::HookedSurvivorDead { .. }
+            | Self::DuplicatePerk { .. }
+            | Self::TooManyPerks { .. }
+            | Self::TooManyOfferings { .. }
+            | Self::InvalidNumAliveSurvivors { .. }
+            | Self::TooManyUpTheAnte { .. } => Severity::Error,
+            Self::UpTheAnteOnDeadSurvivor { .. }
+            | Self::SlipperyMeatOnOtherSurvivor { .. }
+            | Self::BurnerOfferingOnOtherSurvivor { .. } => Severity::Warning,
+        }
+    }
+
+    /// Whether this issue is an error
+    pub fn is_error(&self) -> bool {
+        self.severity() == Severity::Error
+    }
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidHookedSurvivor { index } => {
+                write!(f, "hooked survivor index {} is not between 0 and 3", index)
+            }
+            Self::HookedSurvivorDead { survivor } => {
+                write!(f, "the hooked survivor (player {}) is dead", survivor + 1)
+            }
+            Self::DuplicatePerk { survivor, perk } => write!(
+                f,
+                "player {} has {} equipped more than once",
+                survivor + 1,
+                perk
+            ),
+            Self::TooManyPerks { perk, count } => write!(
+                f,
+                "{} copies of {} requested, but there are only 4 survivors",
+                count, perk
+            ),
+            Self::TooManyOfferings { count } => write!(
+                f,
+                "{} offerings requested, but there are only 4 survivors",
+                count
+            ),
+            Self::InvalidNumAliveSurvivors { count } => write!(
+                f,
+                "{} alive survivors requested, but there must be between 1 and 4",
+                count
+            ),
+            Self::TooManyUpTheAnte {
+                count,
+                num_alive_survivors,
+            } => write!(
+                f,
+                "{} copies of {} requested, but there are only {} alive survivors",
+                count,
+                Perk::UpTheAnte,
+                num_alive_survivors
+            ),
+            Self::UpTheAnteOnDeadSurvivor { survivor } => write!(
+                f,
+                "player {} is dead, so their Up the Ante has no effect",
+                survivor + 1
+            ),
+            Self::SlipperyMeatOnOtherSurvivor { survivor } => write!(
+                f,
+                "player {} is not hooked, so their Slippery Meat has no effect",
+                survivor + 1
+            ),
+            Self::BurnerOfferingOnOtherSurvivor { survivor, offering } => write!(
+                f,
+                "player {} is not hooked, so their {} has no effect",
+                survivor + 1,
+                offering
+            ),
+        }
+    }
+}
+
+/// An error for settings that failed validation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The issues with error severity
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationError {
+    /// Make an error from the error-severity issues in a list, if there are any.
+    pub fn from_issues(issues: impl IntoIterator<Item = ValidationIssue>) -> Option<Self> {
+        let issues: Vec<_> = issues
+            .into_iter()
+            .filter(|issue| issue.is_error())
+            .collect();
+
+        if issues.is_empty() {
+            None
+        } else {
+            Some(Self { issues })
+        }
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "invalid settings: ".fmt(f)?;
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i != 0 {
+                "; ".fmt(f)?;
+            }
+            issue.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Check settings for problems, returning every issue found.
+pub fn validate(settings: &HookEscapeChanceSettings) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    let hooked_survivor = settings.hooked_survivor;
+    match settings.hooked() {
+        None => issues.push(ValidationIssue::InvalidHookedSurvivor {
+            index: hooked_survivor,
+        }),
+        Some(survivor) if !survivor.alive => issues.push(ValidationIssue::HookedSurvivorDead {
+            survivor: hooked_survivor,
+        }),
+        Some(_) => {}
+    }
+
+    for (index, survivor) in settings.survivors.iter().enumerate() {
+        let index = index as u8;
+        let is_hooked = index == hooked_survivor;
+
+        let mut seen_perks = Vec::new();
+        for equipped in survivor.perks.iter().flatten() {
+            if seen_perks.contains(&equipped.perk) {
+                let issue = ValidationIssue::DuplicatePerk {
+                    survivor: index,
+                    perk: equipped.perk,
+                };
+                if !issues.contains(&issue) {
+                    issues.push(issue);
+                }
+            } else {
+                seen_perks.push(equipped.perk);
+            }
+        }
+
+        if !survivor.alive && survivor.perk_tier(Perk::UpTheAnte).is_some() {
+            issues.push(ValidationIssue::UpTheAnteOnDeadSurvivor { survivor: index });
+        }
+
+        if !is_hooked && survivor.perk_tier(Perk::SlipperyMeat).is_some() {
+            issues.push(ValidationIssue::SlipperyMeatOnOtherSurvivor { survivor: index });
+        }
+
+        if let Some(offering) = survivor.offering {
+            if !is_hooked && offering.target() == OfferingTarget::Burner {
+                issues.push(ValidationIssue::BurnerOfferingOnOtherSurvivor {
+                    survivor: index,
+                    offering,
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hook_escape_chance_settings::Tier;
+    use crate::rule_set::RuleSet;
+    use crate::survivor::EquippedPerk;
+
+    #[test]
+    fn default_is_valid() {
+        assert!(validate(&HookEscapeChanceSettings::new()).is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[0].alive = false;
+        settings.survivors[2].perks = [Some(EquippedPerk {
+            perk: Perk::UpTheAnte,
+            tier: Tier::I,
+        }); 4];
+
+        assert_eq!(
+            validate(&settings),
+            vec![
+                ValidationIssue::HookedSurvivorDead { survivor: 0 },
+                ValidationIssue::DuplicatePerk {
+                    survivor: 2,
+                    perk: Perk::UpTheAnte
+                },
+            ]
+        );
+        assert!(settings.try_calculate(&RuleSet::LIVE).is_err());
+
+        settings.hooked_survivor = 4;
+        assert!(validate(&settings).contains(&ValidationIssue::InvalidHookedSurvivor { index: 4 }));
+    }
+
+    #[test]
+    fn warnings() {
+        let mut settings = HookEscapeChanceSettings::new();
+        settings.survivors[1].set_perk_tier(Perk::SlipperyMeat, Some(Tier::III));
+        settings.survivors[2].offering = Some(Offering::ChalkPouch);
+        settings.survivors[3].set_perk_tier(Perk::UpTheAnte, Some(Tier::III));
+        settings.survivors[3].alive = false;
+
+        let issues = validate(&settings);
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::SlipperyMeatOnOtherSurvivor { survivor: 1 },
+                ValidationIssue::BurnerOfferingOnOtherSurvivor {
+                    survivor: 2,
+                    offering: Offering::ChalkPouch
+                },
+                ValidationIssue::UpTheAnteOnDeadSurvivor { survivor: 3 },
+            ]
+        );
+        assert!(issues.iter().all(|issue| !issue.is_error()));
+        assert!(settings.try_calculate(&RuleSet::LIVE).is_ok());
+    }
+
+    #[test]
+    fn from_counts() {
+        let settings =
+            HookEscapeChanceSettings::from_counts(Some(Tier::III), &[Tier::III], 0, 4).unwrap();
+        let chance = settings.calculate(&RuleSet::LIVE);
+        assert!((chance - 0.673059626631).abs() < 0.00000000001);
+
+        assert_eq!(
+            HookEscapeChanceSettings::from_counts(None, &[], 5, 4),
+            Err(ValidationError {
+                issues: vec![ValidationIssue::TooManyOfferings { count: 5 }]
+            })
+        );
+        assert_eq!(
+            HookEscapeChanceSettings::from_counts(None, &[Tier::I; 5], 0, 0),
+            Err(ValidationError {
+                issues: vec![
+                    ValidationIssue::TooManyPerks {
+                        perk: Perk::UpTheAnte,
+                        count: 5
+                    },
+                    ValidationIssue::InvalidNumAliveSurvivors { count: 0 },
+                ]
+            })
+        );
+        assert_eq!(
+            HookEscapeChanceSettings::from_counts(None, &[Tier::III; 3], 0, 2),
+            Err(ValidationError {
+                issues: vec![ValidationIssue::TooManyUpTheAnte {
+                    count: 3,
+                    num_alive_survivors: 2
+                }]
+            })
+        );
+        assert!(HookEscapeChanceSettings::from_counts(None, &[Tier::III; 4], 0, 4).is_ok());
+        assert!(HookEscapeChanceSettings::from_counts(None, &[Tier::III; 2], 0, 2).is_ok());
+    }
+}
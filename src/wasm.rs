@@ -0,0 +1,192 @@
+//! JavaScript bindings, for building the calculator to WebAssembly.
+//!
+//! [`calc_hook_escape_chance`] and [`calc_hook_escape_chance_binomial_theorem`] are
+//! `calcHookEscapeChance` and `calcHookEscapeChanceBinomialTheorem` from `reference/escape-chance.js`,
+//! and [`calculate`] calculates the escape chance of [`Settings`] with the same math as the desktop app.
+//! Functions that take a patch accept `"live"` or `"original"`, and use the live patch if it is missing.
+//! Errors are thrown as strings.
+
+use crate::build_code;
+use crate::escape_distribution::EscapeDistribution;
+use crate::hook_escape_chance_settings::HookEscapeChanceSettings;
+use crate::hook_escape_chance_settings::Tier;
+use crate::rule_set::Patch;
+use crate::serialization;
+use crate::serialization::Format;
+use std::fmt::Display;
+use wasm_bindgen::prelude::*;
+
+/// Turn an error into a JavaScript exception.
+fn to_js_error(e: impl Display) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+/// Parse a patch name, or use the live patch if there is none.
+fn parse_patch(patch: Option<String>) -> Result<Patch, JsValue> {
+    patch.map_or(Ok(Patch::Live), |patch| patch.parse().map_err(to_js_error))
+}
+
+/// Parse a tier from 1 to 3.
+fn parse_tier(tier: u8) -> Result<Tier, JsValue> {
+    tier.to_string().parse().map_err(to_js_error)
+}
+
+/// The survivors in a trial
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Settings(HookEscapeChanceSettings);
+
+#[wasm_bindgen]
+impl Settings {
+    /// 4 alive survivors with no perks or offerings, with player 1 on the hook
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self(HookEscapeChanceSettings::new())
+    }
+
+    /// Make settings from aggregate counts, like the inputs of the reference script.
+    ///
+    /// Tiers are numbers from 1 to 3.
+    #[wasm_bindgen(js_name = fromCounts)]
+    pub fn from_counts(
+        slippery_meat: Option<u8>,
+        up_the_ante: &[u8],
+        num_salty_lips: u8,
+        num_alive_survivors: u8,
+    ) -> Result<Settings, JsValue> {
+        let slippery_meat = slippery_meat.map(parse_tier).transpose()?;
+        let up_the_ante = up_the_ante
+            .iter()
+            .map(|tier| parse_tier(*tier))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        HookEscapeChanceSettings::from_counts(
+            slippery_meat,
+            &up_the_ante,
+            num_salty_lips,
+            num_alive_survivors,
+        )
+        .map(Self)
+        .map_err(to_js_error)
+    }
+
+    /// Load settings saved as JSON by any version of the calculator.
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<Settings, JsValue> {
+        serialization::deserialize(json, Format::Json)
+            .map(Self)
+            .map_err(to_js_error)
+    }
+
+    /// Save these settings as JSON.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsValue> {
+        serialization::serialize(&self.0, Format::Json).map_err(to_js_error)
+    }
+
+    /// Decode a build code.
+    #[wasm_bindgen(js_name = fromBuildCode)]
+    pub fn from_build_code(code: &str) -> Result<Settings, JsValue> {
+        build_code::decode(code).map(Self).map_err(to_js_error)
+    }
+
+    /// Encode these settings as a build code.
+    #[wasm_bindgen(js_name = buildCode)]
+    pub fn build_code(&self) -> String {
+        build_code::encode(&self.0)
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Calculate the escape chance, throwing if the settings don't describe a legal trial.
+#[wasm_bindgen]
+pub fn calculate(settings: &Settings, patch: Option<String>) -> Result<f64, JsValue> {
+    let rules = parse_patch(patch)?.rule_set();
+    settings.0.try_calculate(rules).map_err(to_js_error)
+}
+
+/// Calculate the escape chance as an exact fraction, like `1801/15625`.
+#[wasm_bindgen(js_name = calculateExact)]
+pub fn calculate_exact(settings: &Settings, patch: Option<String>) -> Result<String, JsValue> {
+    let rules = parse_patch(patch)?.rule_set();
+    settings.0.try_calculate(rules).map_err(to_js_error)?;
+
+    Ok(settings.0.calculate_exact(rules).to_string())
+}
+
+/// The chance of escaping on a single attempt
+#[wasm_bindgen(js_name = escapeChancePerAttempt)]
+pub fn escape_chance_per_attempt(
+    settings: &Settings,
+    patch: Option<String>,
+) -> Result<f64, JsValue> {
+    let rules = parse_patch(patch)?.rule_set();
+    Ok(settings.0.distribution(rules).escape_chance_per_attempt)
+}
+
+/// The # of self-unhook attempts
+#[wasm_bindgen(js_name = numTries)]
+pub fn num_tries(settings: &Settings, patch: Option<String>) -> Result<u8, JsValue> {
+    let rules = parse_patch(patch)?.rule_set();
+    Ok(settings.0.distribution(rules).num_tries)
+}
+
+/// `calcHookEscapeChance`: The chance of escaping on any of `num_tries` attempts
+#[wasm_bindgen(js_name = calcHookEscapeChance)]
+pub fn calc_hook_escape_chance(escape_chance: f64, num_tries: u8) -> f64 {
+    EscapeDistribution::new(escape_chance, num_tries).escape_chance()
+}
+
+/// `calcHookEscapeChanceBinomialTheorem`: [`calc_hook_escape_chance`] with the binomial theorem, as the desktop app calculates it
+#[wasm_bindgen(js_name = calcHookEscapeChanceBinomialTheorem)]
+pub fn calc_hook_escape_chance_binomial_theorem(escape_chance: f64, num_tries: u8) -> f64 {
+    EscapeDistribution::new(escape_chance, num_tries).binomial_escape_chance()
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    const TOLERANCE: f64 = 0.00000000001;
+
+    #[wasm_bindgen_test]
+    fn reference_script() {
+        // The script's own inputs: 4 Salty Lips and nothing else
+        let settings = Settings::from_counts(None, &[], 4, 4).unwrap();
+        assert!((escape_chance_per_attempt(&settings, None).unwrap() - 0.16).abs() < TOLERANCE);
+        assert_eq!(num_tries(&settings, None).unwrap(), 3);
+        assert!((calculate(&settings, None).unwrap() - 0.407296).abs() < TOLERANCE);
+        assert!((calc_hook_escape_chance(0.16, 3) - 0.407296).abs() < TOLERANCE);
+        assert!((calc_hook_escape_chance_binomial_theorem(0.16, 3) - 0.407296).abs() < TOLERANCE);
+    }
+
+    #[wasm_bindgen_test]
+    fn settings() {
+        let settings = Settings::from_counts(Some(3), &[3, 2], 2, 3).unwrap();
+        let json = settings.to_json().unwrap();
+        let code = settings.build_code();
+        assert_eq!(Settings::from_json(&json).unwrap().0, settings.0);
+        assert_eq!(Settings::from_build_code(&code).unwrap().0, settings.0);
+
+        let default = Settings::new();
+        assert_eq!(
+            calculate_exact(&default, Some("live".to_string())).unwrap(),
+            "1801/15625"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn errors() {
+        assert!(Settings::from_counts(Some(4), &[], 0, 4).is_err());
+        assert!(Settings::from_counts(None, &[3], 5, 4).is_err());
+        assert!(Settings::from_counts(None, &[3, 3], 0, 2).is_err());
+        assert!(Settings::from_json("{").is_err());
+        assert!(calculate(&Settings::new(), Some("ptb".to_string())).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+/* Exercises the C ABI. Compiled and run by `tests/ffi.rs`. */
+
+#include <math.h>
+#include <stdio.h>
+
+#include "dbd_escape_chance_calculator.h"
+
+#define TOLERANCE 0.00000000001
+
+static int failures = 0;
+
+#define CHECK(condition)                                                    \
+    do {                                                                    \
+        if (!(condition)) {                                                 \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, \
+                    #condition);                                            \
+            failures++;                                                     \
+        }                                                                   \
+    } while (0)
+
+static void default_settings(void) {
+    DbdSettings *settings = dbd_settings_new();
+    double escape_chance = 0.0;
+    double escape_chance_per_attempt = 0.0;
+    uint8_t num_tries = 0;
+
+    CHECK(dbd_calculate(settings, DBD_PATCH_LIVE, &escape_chance) == DBD_STATUS_OK);
+    CHECK(fabs(escape_chance - 0.115264) < TOLERANCE);
+
+    CHECK(dbd_distribution(settings, DBD_PATCH_LIVE, &escape_chance_per_attempt, &num_tries) ==
+          DBD_STATUS_OK);
+    CHECK(fabs(escape_chance_per_attempt - 0.04) < TOLERANCE);
+    CHECK(num_tries == 3);
+
+    dbd_settings_free(settings);
+}
+
+static void mutation(void) {
+    DbdSettings *settings = dbd_settings_new();
+    DbdSettings *copy = NULL;
+    double escape_chance = 0.0;
+    double escape_chance_per_attempt = 0.0;
+    uint8_t num_tries = 0;
+
+    /* Slippery Meat III on the hooked survivor */
+    CHECK(dbd_settings_increment_slippery_meat_tier(settings) == DBD_STATUS_OK);
+    CHECK(dbd_settings_increment_slippery_meat_tier(settings) == DBD_STATUS_OK);
+    CHECK(dbd_settings_increment_slippery_meat_tier(settings) == DBD_STATUS_OK);
+    CHECK(dbd_settings_increment_slippery_meat_tier(settings) == DBD_STATUS_OK);
+
+    /* Up the Ante II on survivor 2, with 3 other alive survivors */
+    CHECK(dbd_settings_increment_up_the_ante_tier(settings, 2) == DBD_STATUS_OK);
+    CHECK(dbd_settings_increment_up_the_ante_tier(settings, 2) == DBD_STATUS_OK);
+    CHECK(dbd_settings_increment_up_the_ante_tier(settings, 2) == DBD_STATUS_OK);
+    CHECK(dbd_settings_decrement_up_the_ante_tier(settings, 2) == DBD_STATUS_OK);
+
+    CHECK(dbd_settings_set_offering(settings, 3, DBD_OFFERING_VIGOS_JAR_OF_SALTY_LIPS) ==
+          DBD_STATUS_OK);
+
+    CHECK(dbd_distribution(settings, DBD_PATCH_LIVE, &escape_chance_per_attempt, &num_tries) ==
+          DBD_STATUS_OK);
+    CHECK(fabs(escape_chance_per_attempt - 0.17) < TOLERANCE);
+    CHECK(num_tries == 6);
+    CHECK(dbd_calculate(settings, DBD_PATCH_LIVE, &escape_chance) == DBD_STATUS_OK);
+    CHECK(fabs(escape_chance - (1.0 - pow(0.83, 6))) < TOLERANCE);
+
+    /* With all 4 survivors alive, the original patch's flat Up the Ante gives the same luck */
+    CHECK(dbd_distribution(settings, DBD_PATCH_ORIGINAL, &escape_chance_per_attempt, &num_tries) ==
+          DBD_STATUS_OK);
+    CHECK(fabs(escape_chance_per_attempt - 0.17) < TOLERANCE);
+
+    /* Copies are independent */
+    copy = dbd_settings_clone(settings);
+    CHECK(dbd_settings_decrement_slippery_meat_tier(copy) == DBD_STATUS_OK);
+    CHECK(dbd_settings_set_perk_tier(copy, 2, DBD_PERK_UP_THE_ANTE, 0) == DBD_STATUS_OK);
+    CHECK(dbd_settings_set_offering(copy, 3, DBD_OFFERING_NONE) == DBD_STATUS_OK);
+    CHECK(dbd_distribution(copy, DBD_PATCH_LIVE, &escape_chance_per_attempt, &num_tries) ==
+          DBD_STATUS_OK);
+    CHECK(fabs(escape_chance_per_attempt - 0.07) < TOLERANCE);
+    CHECK(dbd_calculate(settings, DBD_PATCH_LIVE, &escape_chance) == DBD_STATUS_OK);
+    CHECK(fabs(escape_chance - (1.0 - pow(0.83, 6))) < TOLERANCE);
+
+    /* A dead survivor on the hook is not a legal trial */
+    CHECK(dbd_settings_set_alive(settings, 0, false) == DBD_STATUS_OK);
+    CHECK(dbd_calculate(settings, DBD_PATCH_LIVE, &escape_chance) == DBD_STATUS_INVALID_SETTINGS);
+    CHECK(dbd_settings_set_hooked_survivor(settings, 1) == DBD_STATUS_OK);
+    CHECK(dbd_calculate(settings, DBD_PATCH_LIVE, &escape_chance) == DBD_STATUS_OK);
+
+    dbd_settings_free(copy);
+    dbd_settings_free(settings);
+}
+
+static void from_counts(void) {
+    const uint8_t up_the_ante[] = {3, 2};
+    DbdSettings *settings = NULL;
+    double escape_chance = 0.0;
+
+    CHECK(dbd_settings_from_counts(3, up_the_ante, 2, 2, 3, &settings) == DBD_STATUS_OK);
+    CHECK(dbd_calculate(settings, DBD_PATCH_LIVE, &escape_chance) == DBD_STATUS_OK);
+    CHECK(escape_chance > 0.0 && escape_chance < 1.0);
+    dbd_settings_free(settings);
+
+    settings = NULL;
+    CHECK(dbd_settings_from_counts(4, NULL, 0, 0, 4, &settings) == DBD_STATUS_INVALID_ARGUMENT);
+    CHECK(dbd_settings_from_counts(0, NULL, 0, 5, 4, &settings) == DBD_STATUS_INVALID_SETTINGS);
+    CHECK(dbd_settings_from_counts(0, up_the_ante, 2, 0, 1, &settings) == DBD_STATUS_INVALID_SETTINGS);
+    CHECK(dbd_settings_from_counts(0, NULL, 1, 0, 4, &settings) == DBD_STATUS_NULL_POINTER);
+    CHECK(settings == NULL);
+}
+
+static void errors(void) {
+    DbdSettings *settings = dbd_settings_new();
+    double escape_chance = 0.0;
+
+    CHECK(dbd_calculate(NULL, DBD_PATCH_LIVE, &escape_chance) == DBD_STATUS_NULL_POINTER);
+    CHECK(dbd_calculate(settings, DBD_PATCH_LIVE, NULL) == DBD_STATUS_NULL_POINTER);
+    CHECK(dbd_calculate(settings, 2, &escape_chance) == DBD_STATUS_INVALID_ARGUMENT);
+    CHECK(dbd_settings_set_hooked_survivor(settings, 4) == DBD_STATUS_INVALID_ARGUMENT);
+    CHECK(dbd_settings_set_perk_tier(settings, 0, 2, 1) == DBD_STATUS_INVALID_ARGUMENT);
+    CHECK(dbd_settings_set_perk_tier(settings, 0, DBD_PERK_SLIPPERY_MEAT, 4) ==
+          DBD_STATUS_INVALID_ARGUMENT);
+    CHECK(dbd_settings_set_offering(settings, 0, 7) == DBD_STATUS_INVALID_ARGUMENT);
+    CHECK(dbd_settings_increment_up_the_ante_tier(settings, 4) == DBD_STATUS_INVALID_ARGUMENT);
+    CHECK(dbd_settings_increment_slippery_meat_tier(NULL) == DBD_STATUS_NULL_POINTER);
+    CHECK(dbd_settings_clone(NULL) == NULL);
+    dbd_settings_free(NULL);
+
+    dbd_settings_free(settings);
+}
+
+int main(void) {
+    default_settings();
+    mutation();
+    from_counts();
+    errors();
+
+    if (failures != 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+
+    return 0;
+}
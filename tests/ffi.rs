@@ -0,0 +1,76 @@
+//! Check the committed C header, and compile `tests/ffi.c` against the library and run it.
+#![cfg(feature = "ffi")]
+
+use std::path::PathBuf;
+
+#[test]
+fn header_is_up_to_date() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let generated_path = PathBuf::from(env!("OUT_DIR")).join("dbd_escape_chance_calculator.h");
+    let generated = std::fs::read_to_string(&generated_path).unwrap();
+    let committed =
+        std::fs::read_to_string(manifest_dir.join("include/dbd_escape_chance_calculator.h"))
+            .unwrap();
+
+    // Line endings may be converted on checkout
+    assert!(
+        generated.lines().eq(committed.lines()),
+        "include/dbd_escape_chance_calculator.h is out of date, copy `{}` over it",
+        generated_path.display()
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn c_program() {
+    use std::process::Command;
+
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let tmp_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+
+    // The test binary can't link the C libraries of its own build,
+    // so build them with only the `ffi` feature.
+    // A separate target directory keeps this from waiting on the lock of the outer cargo.
+    let target_dir = tmp_dir.join("ffi-target");
+    let status = Command::new(env!("CARGO"))
+        .arg("rustc")
+        .arg("--manifest-path")
+        .arg(manifest_dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .args([
+            "--lib",
+            "--no-default-features",
+            "--features",
+            "ffi",
+            "--crate-type",
+            "cdylib,staticlib",
+        ])
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "failed to build the C libraries");
+    let library_dir = target_dir.join("debug");
+    let program = tmp_dir.join("ffi");
+
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+    let status = Command::new(compiler)
+        .arg(manifest_dir.join("tests/ffi.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-L")
+        .arg(&library_dir)
+        .arg(format!("-Wl,-rpath,{}", library_dir.display()))
+        .arg("-ldbd_escape_chance_calculator")
+        .arg("-lm")
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "failed to compile tests/ffi.c");
+
+    let status = Command::new(&program)
+        .status()
+        .expect("failed to run the C program");
+    assert!(status.success(), "tests/ffi.c failed");
+}